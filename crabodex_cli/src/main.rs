@@ -1,6 +1,16 @@
+use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use crabodex_lib::core::config::{Config, Forge, HtmlPolicy, ImageMode, ManSection, OutputFormat};
+use crabodex_lib::core::constants::{
+    DEFAULT_ASSETS_DIRECTORY, DEFAULT_MAN_SECTION, DEFAULT_MAX_DEPTH, DEFAULT_MAX_IMAGE_SIZE,
+    DEFAULT_OUTPUT_DIRECTORY, DEFAULT_SITE_PAGE_DEPTH, DEFAULT_TOC_HEADING_LEVEL,
+};
+use crabodex_lib::core::detect_git_repository::{detect_git_repository, GitRepository};
+use crabodex_lib::core::diagnostic::Diagnostic;
+use crabodex_lib::core::export_json::JsonDocument;
+use crabodex_lib::core::import_json::import_json;
 
 const DEFAULT_IGNORE_FOLDERS: &[&str] = &[
     ".git/",
@@ -21,23 +31,97 @@ const DEFAULT_IGNORE_FOLDERS: &[&str] = &[
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     #[clap(long, default_value = ".")]
     root_directory: PathBuf,
 
-    #[clap(long, default_value = "Documentation")]
-    repo_name: String,
+    #[clap(long)]
+    repo_name: Option<String>,
 
     #[clap(long, default_value = "")]
     repo_description: String,
 
-    #[clap(long, default_value = "latest")]
-    commit_hash: String,
+    #[clap(long)]
+    commit_hash: Option<String>,
 
-    #[clap(long, default_value = "")]
-    repo_url: String,
+    #[clap(long)]
+    repo_url: Option<String>,
 
     #[clap(long, use_value_delimiter = true, value_delimiter = ',')]
     ignore_folders: Vec<String>,
+
+    #[clap(long, default_value = "html")]
+    format: OutputFormat,
+
+    #[clap(long, default_value = DEFAULT_OUTPUT_DIRECTORY)]
+    output_directory: PathBuf,
+
+    #[clap(long, default_value_t = DEFAULT_SITE_PAGE_DEPTH)]
+    site_page_depth: usize,
+
+    #[clap(long)]
+    man_path: Option<String>,
+
+    #[clap(long, default_value = DEFAULT_MAN_SECTION)]
+    man_section: ManSection,
+
+    #[clap(long, default_value = "allow")]
+    html_policy: HtmlPolicy,
+
+    #[clap(long, default_value_t = DEFAULT_TOC_HEADING_LEVEL)]
+    toc_heading_level: usize,
+
+    #[clap(long, default_value_t = DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+
+    #[clap(long)]
+    mermaid_script: Option<PathBuf>,
+
+    #[clap(long)]
+    render_dot: bool,
+
+    #[clap(long, default_value = "embed")]
+    images: ImageMode,
+
+    #[clap(long, default_value_t = DEFAULT_MAX_IMAGE_SIZE)]
+    max_image_size: usize,
+
+    #[clap(long, default_value = DEFAULT_ASSETS_DIRECTORY)]
+    assets_directory: PathBuf,
+
+    #[clap(long)]
+    source_ref: Option<String>,
+
+    #[clap(long, default_value = "github")]
+    forge: Forge,
+
+    #[clap(long)]
+    source_link_template: Option<String>,
+
+    #[clap(long)]
+    line_fragment_template: Option<String>,
+
+    #[clap(long)]
+    no_edit_links: bool,
+
+    #[clap(long)]
+    edit_branch: Option<String>,
+
+    #[clap(long)]
+    edit_link_template: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Merges JSON exports of documentations into a single documentation
+    Merge {
+        /// The JSON exports, each one mounted under its repository name or under the path
+        /// given after `=`, ex: `billing.json=Services/Billing`
+        #[clap(required = true)]
+        exports: Vec<String>,
+    },
 }
 
 fn main() -> io::Result<()> {
@@ -51,15 +135,107 @@ fn main() -> io::Result<()> {
     merged_ignore_folders.sort_unstable();
     merged_ignore_folders.dedup();
 
-    let result: String = crabodex_lib::generate(
-        &cli.root_directory,
-        &cli.repo_name,
-        &cli.repo_description,
-        &cli.commit_hash,
-        &cli.repo_url,
-        &merged_ignore_folders
-    );
+    // The repository information not given is read from the local git repository
+    let git_repository: Option<GitRepository> = detect_git_repository(&cli.root_directory);
+    let repo_name: String = cli
+        .repo_name
+        .or_else(|| git_repository.as_ref().map(|repository| repository.name.clone()))
+        .unwrap_or_else(|| "Documentation".to_string());
+    let commit_hash: Option<String> = cli
+        .commit_hash
+        .or_else(|| git_repository.as_ref().and_then(|repository| repository.commit_hash.clone()));
+    let repo_url: String = cli
+        .repo_url
+        .or_else(|| git_repository.as_ref().and_then(|repository| repository.url.clone()))
+        .unwrap_or_default();
+
+    let config: Config = Config {
+        format: cli.format,
+        output_directory: cli.output_directory,
+        site_page_depth: cli.site_page_depth,
+        man_path: cli.man_path,
+        man_section: cli.man_section,
+        html_policy: cli.html_policy,
+        toc_heading_level: cli.toc_heading_level,
+        max_depth: cli.max_depth,
+        mermaid_script: cli.mermaid_script.map(fs::read_to_string).transpose()?,
+        render_dot: cli.render_dot,
+        image_mode: cli.images,
+        max_image_size: cli.max_image_size,
+        assets_directory: cli.assets_directory,
+        // Without a commit hash, the source links point at the default branch, or are left out if it is unknown
+        source_ref: cli.source_ref.or_else(|| {
+            commit_hash.is_none().then(|| {
+                git_repository
+                    .as_ref()
+                    .and_then(|repository| repository.default_branch.clone())
+                    .unwrap_or_default()
+            })
+        }),
+        forge: cli.forge,
+        git_tags: git_repository.as_ref().map(|repository| repository.tags.clone()).unwrap_or_default(),
+        source_link_template: cli.source_link_template,
+        line_fragment_template: cli.line_fragment_template,
+        edit_links: !cli.no_edit_links,
+        // The files are edited on the default branch
+        edit_branch: cli
+            .edit_branch
+            .or_else(|| git_repository.as_ref().and_then(|repository| repository.default_branch.clone())),
+        edit_link_template: cli.edit_link_template,
+    };
+
+    let (result, diagnostics): (String, Vec<Diagnostic>) = match cli.command {
+        Some(Command::Merge { exports }) => {
+            let documents: Vec<(JsonDocument, Vec<String>)> = exports
+                .iter()
+                .map(|export| read_export(export))
+                .collect::<io::Result<_>>()?;
+            crabodex_lib::merge(
+                &cli.root_directory,
+                documents,
+                &repo_name,
+                &cli.repo_description,
+                commit_hash.as_deref().unwrap_or("latest"),
+                &repo_url,
+                &config
+            )
+        }
+        None => crabodex_lib::generate_with_config(
+            &cli.root_directory,
+            &repo_name,
+            &cli.repo_description,
+            commit_hash.as_deref().unwrap_or("latest"),
+            &repo_url,
+            &merged_ignore_folders,
+            &config
+        ),
+    };
+
+    for diagnostic in diagnostics {
+        eprintln!("warning: {diagnostic}");
+    }
 
     io::stdout().write_all(result.as_bytes())?;
     Ok(())
 }
+
+/// Reads a JSON export given as `path` or `path=mount/path`, with the path it is mounted under.
+fn read_export(export: &str) -> io::Result<(JsonDocument, Vec<String>)> {
+    let (path, mount_path): (&str, Option<&str>) = match export.split_once('=') {
+        Some((path, mount_path)) => (path, Some(mount_path)),
+        None => (export, None),
+    };
+
+    let document: JsonDocument = import_json(&fs::read_to_string(path)?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {error}")))?;
+    // The documentation is mounted under the name of its repository by default
+    let mount_path: Vec<String> = mount_path
+        .unwrap_or(document.repository.name.as_str())
+        .split('/')
+        .map(str::trim)
+        .filter(|section| !section.is_empty())
+        .map(ToString::to_string)
+        .collect();
+
+    Ok((document, mount_path))
+}
@@ -0,0 +1,47 @@
+use crate::core::escape_html::escape_html;
+use crate::DocNode;
+
+/// Builds the breadcrumb trail of a node, from the top of the documentation down to the node.
+///
+/// The ancestors are found from the `path` of the node, each of them links to its section.
+///
+/// # Arguments
+/// * `node` - The node the breadcrumbs lead to.
+///
+/// # Returns
+/// The breadcrumbs as an HTML string.
+///
+#[must_use]
+pub fn build_breadcrumbs(node: &DocNode) -> String {
+    let sections: Vec<&str> = node.path.split(" > ").collect();
+    let mut html: String = String::from("<div class=\"breadcrumbs\" aria-label=\"Breadcrumb\">");
+
+    for (index, section) in sections.iter().enumerate().take(sections.len() - 1) {
+        let ancestor: DocNode = DocNode::new(section, &sections[..=index].join(" > "));
+        html.push_str(&format!(
+            "<a href=\"#{}\">{}</a><span>›</span>",
+            escape_html(&ancestor.id()),
+            escape_html(section)
+        ));
+    }
+
+    html.push_str(&format!(
+        "<span aria-current=\"location\">{}</span></div>",
+        escape_html(&node.title)
+    ));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_breadcrumbs() {
+        let node: DocNode = DocNode::new("Rounding", "Pricing > Discounts > Rounding");
+
+        let breadcrumbs: String = build_breadcrumbs(&node);
+
+        assert_eq!(breadcrumbs, "<div class=\"breadcrumbs\" aria-label=\"Breadcrumb\"><a href=\"#pricing\">Pricing</a><span>›</span><a href=\"#pricing-&gt;-discounts\">Discounts</a><span>›</span><span aria-current=\"location\">Rounding</span></div>");
+    }
+}
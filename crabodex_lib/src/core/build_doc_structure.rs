@@ -1,7 +1,9 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use crate::core::constants::DEFAULT_FRONT_MATTER_PREFIX;
-use crate::core::parse_front_matter::parse_front_matter;
+use crate::core::parse_front_matter::{
+    front_matter_source, parse_front_matter, parse_front_matter_description, parse_front_matter_tags,
+};
+use crate::core::strip_front_matter::{content_lines, strip_front_matter};
 use crate::DocNode;
 
 /// Build the document structure recursively.
@@ -46,17 +48,18 @@ pub fn build_doc_structure(files: &[PathBuf], root_directory: &Path) -> DocNode
             });
         }
 
-        if let Some(content_start) = content.find(DEFAULT_FRONT_MATTER_PREFIX).and_then(|i| {
-            content[i + DEFAULT_FRONT_MATTER_PREFIX.len()..].find(DEFAULT_FRONT_MATTER_PREFIX)
-        }) {
-            let content_start = content.find(DEFAULT_FRONT_MATTER_PREFIX).unwrap() + DEFAULT_FRONT_MATTER_PREFIX.len() + content_start + DEFAULT_FRONT_MATTER_PREFIX.len();
-            current.content = Some(content[content_start..].trim().to_string());
-        }
+        let lines: Option<(usize, usize)> = content_lines(&content);
+        current.start_line = lines.map(|(start_line, _)| start_line);
+        current.end_line = lines.map(|(_, end_line)| end_line);
+        current.content = Some(strip_front_matter(&content).to_string());
 
         current.title = title;
         current.path = current_path;
         current.depth = path.len();
         current.position = position;
+        current.tags = parse_front_matter_tags(&content);
+        current.description = parse_front_matter_description(&content);
+        current.front_matter = front_matter_source(&content).map(|front_matter| front_matter.trim().to_string());
         current.file_path = Some(file.to_string_lossy().to_string());
     }
 
@@ -80,5 +83,6 @@ mod tests {
         assert!(doc_structure.children.contains_key("Getting Started"));
         let getting_started: &DocNode = &doc_structure.children["Getting Started"];
         assert_eq!(getting_started.title, "Getting Started");
+        assert_eq!((getting_started.start_line, getting_started.end_line), (Some(7), Some(14)));
     }
 }
@@ -1,34 +1,40 @@
+use crate::core::collect_headings::{collect_headings, Heading};
+use crate::core::config::Config;
+use crate::core::escape_html::escape_html;
 use crate::DocNode;
 
 /// Build the navigation for the documentation recursively.
 /// 
+/// The markdown headings of a node are listed under it, before its children,
+/// down to the `toc_heading_level` of the configuration.
+/// 
 /// # Arguments
 /// * `node` - The current node in the document structure.
+/// * `config` - The configuration of the documentation generation.
 /// 
 /// # Returns
 /// The navigation for the documentation as an HTML string.
 /// 
 #[must_use]
-pub fn build_navigation(node: &DocNode) -> String {
+pub fn build_navigation(node: &DocNode, config: &Config) -> String {
+    let headings: Vec<Heading> = collect_toc_headings(node, config);
+    build_node_list(node, &headings, config)
+}
+
+/// Builds the list of a node from its already collected headings, recursively.
+fn build_node_list(node: &DocNode, headings: &[Heading], config: &Config) -> String {
     let mut html: String = String::default();
     html.push_str("<ul>");
 
-    let mut children: Vec<(&String, &DocNode)> = node.children.iter().collect();
-    children.sort_by(|a, b| {
-        match (a.1.position, b.1.position) {
-            (Some(pos_a), Some(pos_b)) => pos_a.cmp(&pos_b),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.1.title.cmp(&b.1.title),
-        }
-    });
+    html.push_str(&build_heading_items(headings));
 
-    for (_, child) in children {
-        let child_id: String = child.path.replace(' ', "-").to_lowercase();
+    for child in node.sorted_children() {
+        let child_id: String = child.id();
         html.push_str(&format!("<li><a href=\"#{}\">{}</a>", child_id, child.title));
 
-        if !child.children.is_empty() {
-            html.push_str(&build_navigation(child));
+        let child_headings: Vec<Heading> = collect_toc_headings(child, config);
+        if !child.children.is_empty() || !child_headings.is_empty() {
+            html.push_str(&build_node_list(child, &child_headings, config));
         }
 
         html.push_str("</li>");
@@ -38,6 +44,44 @@ pub fn build_navigation(node: &DocNode) -> String {
     html
 }
 
+/// Collects the headings of a node that are shallow enough to be listed in the navigation.
+fn collect_toc_headings(node: &DocNode, config: &Config) -> Vec<Heading> {
+    collect_headings(node)
+        .into_iter()
+        .filter(|heading| heading.level <= config.toc_heading_level)
+        .collect()
+}
+
+/// Builds the list items of the headings, nesting the deeper ones under the previous shallower one.
+fn build_heading_items(headings: &[Heading]) -> String {
+    let mut html: String = String::default();
+    let mut index: usize = 0;
+
+    while index < headings.len() {
+        let heading: &Heading = &headings[index];
+        let sub_headings_count: usize = headings[index + 1..]
+            .iter()
+            .take_while(|sub_heading| sub_heading.level > heading.level)
+            .count();
+
+        html.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            escape_html(&heading.id),
+            escape_html(&heading.title)
+        ));
+        if sub_headings_count > 0 {
+            html.push_str("<ul>");
+            html.push_str(&build_heading_items(&headings[index + 1..=index + sub_headings_count]));
+            html.push_str("</ul>");
+        }
+        html.push_str("</li>");
+
+        index += sub_headings_count + 1;
+    }
+
+    html
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -53,8 +97,24 @@ mod tests {
 
         let markdown_files: Vec<PathBuf> = find_markdown_files(test_dir.clone(), &["ignored_test_files".to_string()]);
         let doc_structure: DocNode = build_doc_structure(&markdown_files, test_dir.as_path());
-        let navigation: String = build_navigation(&doc_structure);
+        let navigation: String = build_navigation(&doc_structure, &Config::default());
 
         assert_eq!(navigation, "<ul><li><a href=\"#getting-started\">Getting Started</a><ul><li><a href=\"#getting-started->-configuration\">Configuration</a></li></ul></li><li><a href=\"#features\">Features</a><ul><li><a href=\"#features->-feature-one\">Feature one</a></li></ul></li><li><a href=\"#domain\">Domain</a><ul><li><a href=\"#domain->-subdomain-one\">Subdomain one</a></li><li><a href=\"#domain->-subdomain-two\">Subdomain two</a></li></ul></li></ul>");
     }
+
+    #[test]
+    fn test_build_navigation_headings() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Rules", "Rules");
+        node.content = Some("## Pricing\n\n### Discounts\n\n#### Details\n\n## Taxes".to_string());
+        node.children.insert("Refunds".to_string(), DocNode::new("Refunds", "Rules > Refunds"));
+        root.children.insert("Rules".to_string(), node);
+
+        let navigation: String = build_navigation(&root, &Config::default());
+        assert_eq!(navigation, "<ul><li><a href=\"#rules\">Rules</a><ul><li><a href=\"#rules--pricing\">Pricing</a><ul><li><a href=\"#rules--discounts\">Discounts</a></li></ul></li><li><a href=\"#rules--taxes\">Taxes</a></li><li><a href=\"#rules->-refunds\">Refunds</a></li></ul></li></ul>");
+
+        let config: Config = Config { toc_heading_level: 0, ..Config::default() };
+        let navigation: String = build_navigation(&root, &config);
+        assert_eq!(navigation, "<ul><li><a href=\"#rules\">Rules</a><ul><li><a href=\"#rules->-refunds\">Refunds</a></li></ul></li></ul>");
+    }
 }
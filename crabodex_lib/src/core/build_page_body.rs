@@ -1,14 +1,39 @@
-use pulldown_cmark::{html::push_html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::collections::HashSet;
+use std::path::Path;
+
+use pulldown_cmark::{html::push_html, CodeBlockKind, CowStr, Event, HeadingLevel, Parser, Tag, TagEnd};
 use syntect::{highlighting::ThemeSet, html::highlighted_html_for_string, parsing::SyntaxSet};
 
+use crate::core::collect_headings::{collect_headings, Heading};
+use crate::core::build_breadcrumbs::build_breadcrumbs;
+use crate::core::build_sibling_links::build_sibling_links;
+use crate::core::build_source_link::{build_commit_link, build_edit_link, build_source_link, source_link_title};
+use crate::core::config::{Config, HtmlPolicy};
+use crate::core::constants::MAX_HTML_HEADING_LEVEL;
+use crate::core::diagnostic::Diagnostic;
+use crate::core::escape_html::escape_html;
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_diagram::{render_dot, render_mermaid};
+use crate::core::render_admonitions::{expand_admonition_containers, render_admonitions};
+use crate::core::render_math::render_math;
+use crate::core::repository::Repository;
+use crate::core::resolve_image_url::{rebase_image_url, resolve_image_url};
+use crate::core::resolve_includes::{
+    fence_attribute, follow_transclusion_marker, paired_transclusion_markers, transclusion_marker, TransclusionMarker,
+};
+use crate::core::sanitize_html::{is_safe_url, HtmlSanitizer};
 use crate::DocNode;
 
 /// Builds the body of the HTML page from the document structure.
 ///
 /// # Arguments
 /// * `root` - The root of the document structure.
+/// * `root_directory` - The root directory where the markdown files are stored.
 /// * `depth` - The depth of the current node in the document structure.
 /// * `repo_url` - The URL of the repository where the markdown files are stored.
+/// * `source_ref` - The branch, tag or commit the source links point at.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while building the body are added to.
 ///
 /// # Returns
 /// The body of the HTML page.
@@ -17,111 +42,399 @@ use crate::DocNode;
 /// If the Front Matter is invalid.
 ///
 #[must_use]
-pub fn build_page_body(root: &DocNode, depth: usize, repo_url: &str) -> String {
+pub fn build_page_body(
+    root: &DocNode,
+    root_directory: &Path,
+    depth: usize,
+    repo_url: &str,
+    source_ref: &str,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    build_section(root, root_directory, depth, (None, None), (repo_url, source_ref), config, diagnostics)
+}
+
+/// Builds a section of the page and its subsections recursively.
+///
+/// # Arguments
+/// * `root` - The node of the section.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `depth` - The depth of the node in the document structure.
+/// * `siblings` - The previous and next siblings of the node, in display order.
+/// * `repository` - The URL of the repository where the markdown files are stored, and the ref the source links point at.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while building the section are added to.
+///
+/// # Returns
+/// The section as an HTML string.
+///
+#[must_use]
+pub fn build_section(
+    root: &DocNode,
+    root_directory: &Path,
+    depth: usize,
+    siblings: (Option<&DocNode>, Option<&DocNode>),
+    repository: (&str, &str),
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    // The sections imported from another repository link to their own sources,
+    // and to their commit where they start
+    let node_repository: Option<&Repository> = root
+        .repository
+        .as_ref()
+        .filter(|node_repository| (node_repository.url.as_str(), node_repository.source_ref.as_str()) != repository);
+    let repository: (&str, &str) = match node_repository {
+        Some(node_repository) => (&node_repository.url, &node_repository.source_ref),
+        None => repository,
+    };
+    // The links to the imported sections follow the format of their forge
+    let link_config: Option<Config> = node_repository.map(|node_repository| node_repository.link_config(config));
+    let config: &Config = link_config.as_ref().unwrap_or(config);
+    let (repo_url, source_ref): (&str, &str) = repository;
     let mut html: String = String::default();
-    let mut options: Options = Options::empty();
-    options.insert(Options::ENABLE_TABLES);
-    options.insert(Options::ENABLE_TASKLISTS);
-    options.insert(Options::ENABLE_DEFINITION_LIST);
-    options.insert(Options::ENABLE_FOOTNOTES);
-    options.insert(Options::ENABLE_GFM);
-    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
-    options.insert(Options::ENABLE_MATH);
-    options.insert(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
-    options.insert(Options::ENABLE_SMART_PUNCTUATION);
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    let syntax_set = SyntaxSet::load_defaults_newlines();
-    let theme_set = ThemeSet::load_defaults();
-    let syntax = syntax_set.find_syntax_by_extension("rs").unwrap();
-    let theme = &theme_set.themes["base16-eighties.dark"];
 
     if depth > 0 {
-        let header_level: usize = std::cmp::min(depth, 6);
-        let id: String = root.path.replace(' ', "-").to_lowercase();
+        let (heading_start, heading_end): (String, String) = heading_tags(depth, &root.id());
 
-        if root.file_path.is_some() {
-            let github_link: String =
-                format!("{}/blob/main/{}", repo_url, root.clone().file_path.unwrap());
+        let lines: Option<(usize, usize)> = root.start_line.zip(root.end_line);
+        let source_link: Option<String> = root
+            .file_path
+            .as_deref()
+            .and_then(|file_path| build_source_link(repo_url, source_ref, file_path, lines, config));
+        if let Some(source_link) = source_link {
+            let github_link: String = escape_html(&source_link);
             html.push_str(&format!(
-                "<h{0} id=\"{1}\"><span>{2} <a href=\"{3}\" title=\"View on Github\" class=\"gh-icon\"></a></span></h{0}>",
-                header_level,
-                id,
+                "{}<span>{} <a href=\"{}\" title=\"{}\" class=\"gh-icon\"></a></span>{}",
+                heading_start,
                 root.title,
-                github_link
+                github_link,
+                source_link_title(config),
+                heading_end
             ));
         } else {
             html.push_str(&format!(
-                "<h{0} id=\"{1}\"><span>{2}</span></h{0}>",
-                header_level, id, root.title
+                "{}<span>{}</span>{}",
+                heading_start, root.title, heading_end
+            ));
+        }
+
+        let breadcrumbs: String = if depth > 1 { build_breadcrumbs(root) } else { String::default() };
+        let sibling_links: String = build_sibling_links(siblings.0, siblings.1);
+        let edit_link: String = root
+            .file_path
+            .as_deref()
+            .and_then(|file_path| build_edit_link(repo_url, file_path, config))
+            .map(|edit_link| format!(
+                "<a href=\"{}\" class=\"edit-link\" target=\"_blank\">Suggest an edit</a>",
+                escape_html(&edit_link)
+            ))
+            .unwrap_or_default();
+        let commit_link: String = node_repository
+            .map(|node_repository| format!(
+                "<a href=\"{}\" class=\"commit-link\" target=\"_blank\">{} @ {}</a>",
+                escape_html(&build_commit_link(&node_repository.url, &node_repository.commit_hash, config)),
+                escape_html(&node_repository.name),
+                escape_html(&node_repository.commit_hash)
+            ))
+            .unwrap_or_default();
+        if !breadcrumbs.is_empty() || !sibling_links.is_empty() || !edit_link.is_empty() || !commit_link.is_empty() {
+            html.push_str(&format!(
+                "<div class=\"section-navigation depth-{depth}\">{breadcrumbs}{sibling_links}{commit_link}{edit_link}</div>"
             ));
         }
 
         if let Some(content) = &root.content {
-            let mut content_html: String = String::with_capacity(&content.len() * 3 / 2);
-            let parser: Parser = Parser::new_ext(content, options);
-
-            // We'll build a new vector of events since we can only consume the parser once
-            let mut new_p = Vec::new();
-            // As we go along, we'll want to highlight code in bundles, not lines
-            let mut to_highlight = String::new();
-            // And track a little bit of state
-            let mut in_code_block = false;
-
-            for event in parser {
-                match event {
-                    Event::Start(Tag::CodeBlock(_)) => {
-                        in_code_block = true;
+            let content_html: String = render_content(root, content, root_directory, depth, repository, config, diagnostics);
+            html.push_str(&format!(
+                "<div class=\"depth-{depth}\">{content_html}</div>"
+            ));
+        }
+    }
+
+    let children: Vec<&DocNode> = root.sorted_children();
+    for (index, child) in children.iter().enumerate() {
+        let previous: Option<&DocNode> = index.checked_sub(1).map(|previous| children[previous]);
+        let next: Option<&DocNode> = children.get(index + 1).copied();
+        html.push_str(&build_section(child, root_directory, depth + 1, (previous, next), repository, config, diagnostics));
+    }
+
+    html
+}
+
+/// Renders the markdown content of a node to HTML.
+///
+/// The headings of the content are demoted below the heading of the node
+/// and get the anchors listed in the navigation. The content of the included
+/// markdown files is wrapped in a block linking to its source, the markers
+/// around it that do not open and close each other are dropped.
+///
+/// # Arguments
+/// * `node` - The node being rendered.
+/// * `content` - The markdown content of the node.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `depth` - The depth of the node in the document structure.
+/// * `repository` - The URL of the repository, and the ref the source links point at.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while rendering are added to.
+///
+/// # Returns
+/// The content as an HTML string.
+///
+#[must_use]
+pub fn render_content(
+    node: &DocNode,
+    content: &str,
+    root_directory: &Path,
+    depth: usize,
+    repository: (&str, &str),
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let rust_syntax = syntax_set.find_syntax_by_extension("rs").unwrap();
+    let theme = &theme_set.themes["base16-eighties.dark"];
+
+    let mut content_html: String = String::with_capacity(content.len() * 3 / 2);
+    let content: String = expand_admonition_containers(content);
+    let events: Vec<Event> = Parser::new_ext(&content, markdown_options()).collect();
+    // Only the markers of the included files that open and close each other are rendered
+    let transclusion_markers: HashSet<usize> = paired_transclusion_markers(&events);
+    // The headings come in the same order as the events, with their anchors
+    let mut headings = collect_headings(node).into_iter();
+
+    // We'll build a new vector of events since we can only consume the parser once
+    let mut new_p = Vec::new();
+    // As we go along, we'll want to highlight code in bundles, not lines
+    let mut to_highlight = String::new();
+    // And track a little bit of state
+    let mut in_code_block = false;
+    let mut code_info: String = String::default();
+    // The raw HTML is sanitized fragment by fragment, the sanitizer keeps the state between them
+    let mut sanitizer: HtmlSanitizer = HtmlSanitizer::default();
+    // The file the current content comes from, the last included one or the file of the node
+    let mut file_paths: Vec<String> = node.file_path.iter().cloned().collect();
+
+    for (index, event) in events.into_iter().enumerate() {
+        match event {
+            Event::Start(Tag::Heading { level, id, classes, attrs }) => {
+                let heading: Option<Heading> = headings.next();
+                let id: Option<CowStr> = heading.map(|heading| CowStr::Boxed(heading.id.into())).or(id);
+                match HeadingLevel::try_from(level as usize + depth) {
+                    Ok(level) => new_p.push(Event::Start(Tag::Heading { level, id, classes, attrs })),
+                    // Beyond h6, the heading is a styled element with its level as an aria attribute
+                    Err(_) => {
+                        let id: String = escape_html(id.as_deref().unwrap_or_default());
+                        let (heading_start, _) = heading_tags(level as usize + depth, &id);
+                        new_p.push(Event::Html(CowStr::Boxed(heading_start.into())));
                     }
-                    Event::End(TagEnd::CodeBlock) => {
-                        if in_code_block {
-                            // Format the whole multi-line code block as HTML all at once
-                            let html = highlighted_html_for_string(
-                                &to_highlight,
-                                &syntax_set,
-                                syntax,
-                                theme,
-                            )
-                            .unwrap();
-                            // And put it into the vector
-                            new_p.push(Event::Html(CowStr::Boxed(html.into())));
-                            to_highlight = String::new();
-                            in_code_block = false;
-                        }
+                }
+            }
+            Event::End(TagEnd::Heading(level)) => {
+                let (_, heading_end) = heading_tags(level as usize + depth, "");
+                new_p.push(Event::Html(CowStr::Boxed(heading_end.into())));
+            }
+            // Unless the raw HTML is allowed, the links and images are held to the same schemes as in the raw HTML
+            Event::Start(Tag::Link { link_type, dest_url, title, id })
+                if config.html_policy != HtmlPolicy::Allow && !is_safe_url(&dest_url) =>
+            {
+                push_unsafe_url_diagnostic(&dest_url, file_paths.last().map(String::as_str), diagnostics);
+                new_p.push(Event::Start(Tag::Link { link_type, dest_url: CowStr::Borrowed(""), title, id }));
+            }
+            Event::Start(Tag::Image { link_type, dest_url, title, id })
+                if config.html_policy != HtmlPolicy::Allow && !is_safe_url(&dest_url) =>
+            {
+                push_unsafe_url_diagnostic(&dest_url, file_paths.last().map(String::as_str), diagnostics);
+                new_p.push(Event::Start(Tag::Image { link_type, dest_url: CowStr::Borrowed(""), title, id }));
+            }
+            // The local images are embedded or copied so the page can display them,
+            // the ones of the sections imported from another repository are linked in it
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                let file_path: Option<&str> = file_paths.last().map(String::as_str);
+                let is_imported: bool = node.repository.is_some() && !repository.0.is_empty();
+                let resolved: Option<Result<Option<String>, String>> = file_path.map(|file_path| {
+                    if is_imported {
+                        rebase_image_url(&dest_url, file_path, repository, config)
+                    } else {
+                        resolve_image_url(&dest_url, file_path, root_directory, config)
                     }
-                    Event::Text(t) => {
-                        if in_code_block {
-                            // If we're in a code block, build up the string of text
-                            to_highlight.push_str(&t);
-                        } else {
-                            new_p.push(Event::Text(t));
-                        }
+                });
+                let dest_url: CowStr = match resolved {
+                    Some(Ok(Some(url))) => CowStr::Boxed(url.into()),
+                    Some(Err(error)) => {
+                        diagnostics.push(Diagnostic::new(
+                            file_path,
+                            &format!("could not resolve image `{dest_url}`: {error}"),
+                        ));
+                        dest_url
                     }
-                    e => {
-                        new_p.push(e);
+                    _ => dest_url,
+                };
+                new_p.push(Event::Start(Tag::Image { link_type, dest_url, title, id }));
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                code_info = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::default(),
+                };
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if in_code_block {
+                    let code_language: String = code_info.split_whitespace().next().unwrap_or_default().to_lowercase();
+                    // The code is highlighted as rust when its language is unknown
+                    let syntax = syntax_set.find_syntax_by_token(&code_language).unwrap_or(rust_syntax);
+                    // Format the whole multi-line code block as HTML all at once
+                    let highlight = |code: &str| {
+                        highlighted_html_for_string(code, &syntax_set, syntax, theme).unwrap()
+                    };
+                    // Diagrams are rendered as such instead of being highlighted
+                    let html: String = match code_language.as_str() {
+                        "mermaid" => render_mermaid(&to_highlight),
+                        "dot" if config.render_dot => render_dot(&to_highlight).unwrap_or_else(|error| {
+                            diagnostics.push(Diagnostic::new(
+                                node.file_path.as_deref(),
+                                &format!("could not render dot graph: {error}"),
+                            ));
+                            highlight(&to_highlight)
+                        }),
+                        _ => highlight(&to_highlight),
+                    };
+                    // The included code snippets link back to their source
+                    let html: String = match fence_attribute(&code_info, "source") {
+                        Some(source) => code_snippet_figure(&html, source, fence_attribute(&code_info, "lines"), repository, config),
+                        None => html,
+                    };
+                    // And put it into the vector
+                    new_p.push(Event::Html(CowStr::Boxed(html.into())));
+                    to_highlight = String::new();
+                    in_code_block = false;
+                }
+            }
+            Event::Text(t) => {
+                if in_code_block {
+                    // If we're in a code block, build up the string of text
+                    to_highlight.push_str(&t);
+                } else {
+                    new_p.push(Event::Text(t));
+                }
+            }
+            Event::InlineMath(tex) => {
+                new_p.push(Event::InlineHtml(CowStr::Boxed(render_math_or_source(node, &tex, false, diagnostics).into())));
+            }
+            Event::DisplayMath(tex) => {
+                new_p.push(Event::InlineHtml(CowStr::Boxed(render_math_or_source(node, &tex, true, diagnostics).into())));
+            }
+            // The markers written in the markdown files themselves are dropped
+            Event::Html(raw) if transclusion_marker(&raw).is_some() && !transclusion_markers.contains(&index) => {}
+            // The content of the included markdown files links back to their source
+            Event::Html(raw) if transclusion_marker(&raw).is_some() => {
+                let marker: Option<TransclusionMarker> = transclusion_marker(&raw);
+                if let Some(marker) = &marker {
+                    follow_transclusion_marker(&mut file_paths, marker);
+                }
+                let html: String = match marker {
+                    Some(TransclusionMarker::Start { path, lines }) => {
+                        let source: String = match build_source_link(repository.0, repository.1, path, lines, config) {
+                            Some(source_link) => format!(
+                                "<a href=\"{}\" title=\"{}\">{}</a>",
+                                escape_html(&source_link),
+                                source_link_title(config),
+                                escape_html(path)
+                            ),
+                            None => escape_html(path),
+                        };
+                        format!("<div class=\"transclusion\"><p class=\"transclusion-source\">Included from {source}</p>")
                     }
+                    _ => "</div>".to_string(),
+                };
+                new_p.push(Event::Html(CowStr::Boxed(html.into())));
+            }
+            Event::Html(raw) | Event::InlineHtml(raw) if config.html_policy != HtmlPolicy::Allow => {
+                if config.html_policy == HtmlPolicy::Escape {
+                    new_p.push(Event::Text(raw));
+                } else {
+                    new_p.push(Event::Html(CowStr::Boxed(sanitizer.sanitize(&raw).into())));
                 }
             }
-
-            push_html(&mut content_html, new_p.into_iter());
-            html.push_str(&format!(
-                "<div class=\"depth-{depth}\">{content_html}</div>"
-            ));
+            e => {
+                new_p.push(e);
+            }
         }
     }
 
-    let mut children: Vec<(&String, &DocNode)> = root.children.iter().collect();
-    children.sort_by(|a, b| match (a.1.position, b.1.position) {
-        (Some(pos_a), Some(pos_b)) => pos_a.cmp(&pos_b),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => a.1.title.cmp(&b.1.title),
-    });
-
-    for (_, child) in children {
-        html.push_str(&build_page_body(child, depth + 1, repo_url));
+    if !sanitizer.stripped().is_empty() {
+        diagnostics.push(Diagnostic::new(
+            node.file_path.as_deref(),
+            &format!("stripped raw HTML: {}", sanitizer.stripped().join(", ")),
+        ));
     }
 
-    html
+    // The admonitions are rendered last so their HTML is not affected by the HTML policy
+    push_html(&mut content_html, render_admonitions(new_p).into_iter());
+    content_html
+}
+
+/// Warns about the destination of a link or an image removed because of its scheme.
+fn push_unsafe_url_diagnostic(url: &str, file_path: Option<&str>, diagnostics: &mut Vec<Diagnostic>) {
+    diagnostics.push(Diagnostic::new(file_path, &format!("removed the unsafe URL `{url}`")));
+}
+
+/// Renders a formula to MathML, or keeps its TeX source visible when it can not be converted.
+fn render_math_or_source(node: &DocNode, tex: &str, display: bool, diagnostics: &mut Vec<Diagnostic>) -> String {
+    render_math(tex, display).unwrap_or_else(|error| {
+        diagnostics.push(Diagnostic::new(
+            node.file_path.as_deref(),
+            &format!("could not render math `{tex}`: {error}"),
+        ));
+        let (class, delimiter): (&str, &str) = if display { ("math math-display", "$$") } else { ("math math-inline", "$") };
+        format!("<code class=\"{class}\">{delimiter}{}{delimiter}</code>", escape_html(tex))
+    })
+}
+
+/// Wraps a highlighted code snippet in a figure whose caption links to the lines of its source file.
+fn code_snippet_figure(code_html: &str, source: &str, lines: Option<&str>, repository: (&str, &str), config: &Config) -> String {
+    let (repo_url, source_ref): (&str, &str) = repository;
+    let lines: Option<(usize, usize)> = lines
+        .and_then(|lines| lines.split_once('-'))
+        .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)));
+    let caption: String = match lines {
+        Some((start, end)) => format!("{source}, lines {start}-{end}"),
+        None => source.to_string(),
+    };
+
+    let caption: String = match build_source_link(repo_url, source_ref, source, lines, config) {
+        Some(source_link) => format!(
+            "<a href=\"{}\" title=\"{}\">{}</a>",
+            escape_html(&source_link),
+            source_link_title(config),
+            escape_html(&caption)
+        ),
+        None => escape_html(&caption),
+    };
+    format!("<figure class=\"code-snippet\">{code_html}<figcaption>{caption}</figcaption></figure>")
+}
+
+/// Builds the opening and closing tags of a heading.
+///
+/// HTML only has six levels of headings. Beyond that, the heading is a
+/// styled element carrying its level in an `aria-level` attribute.
+fn heading_tags(level: usize, id: &str) -> (String, String) {
+    let id_attribute: String = if id.is_empty() {
+        String::default()
+    } else {
+        format!(" id=\"{id}\"")
+    };
+
+    if level <= MAX_HTML_HEADING_LEVEL {
+        (format!("<h{level}{id_attribute}>"), format!("</h{level}>"))
+    } else {
+        (
+            format!("<div role=\"heading\" aria-level=\"{level}\" class=\"deep-heading\"{id_attribute}>"),
+            "</div>".to_string(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +442,7 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::core::build_doc_structure::build_doc_structure;
+    use crate::core::config::Forge;
     use crate::find_markdown_files;
 
     use super::*;
@@ -141,10 +455,275 @@ mod tests {
 
         let markdown_files: Vec<PathBuf> = find_markdown_files(test_dir.clone(), &[]);
         let doc_structure: DocNode = build_doc_structure(&markdown_files, test_dir.as_path());
-        let page_body: String =
-            build_page_body(&doc_structure, 0, "https://github.com/example/repo");
+        let page_body: String = build_page_body(
+            &doc_structure,
+            test_dir.as_path(),
+            0,
+            "https://github.com/example/repo",
+            "main",
+            &Config::default(),
+            &mut Vec::new(),
+        );
+
+        assert!(page_body.contains("<h1 id=\"getting-started\"><span>Getting Started <a href=\"https://github.com/example/repo/blob/main/file1.md?plain=1#L7-L14\" title=\"View on GitHub\" class=\"gh-icon\"></a></span></h1>"));
+        assert!(page_body.contains("<h2 id=\"getting-started->-configuration\"><span>Configuration <a href=\"https://github.com/example/repo/blob/main/sub_dir_2/file2.md?plain=1#L7-L56\" title=\"View on GitHub\" class=\"gh-icon\"></a></span></h2>"));
+    }
+
+    #[test]
+    fn test_build_page_body_html_policy() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Rules", "Rules");
+        node.depth = 1;
+        node.file_path = Some("rules.md".to_string());
+        node.content = Some("<div onclick=\"steal()\">Rule</div>\n\nSome <b>bold</b> text".to_string());
+        root.children.insert("Rules".to_string(), node);
+        let repo_url: &str = "https://github.com/example/repo";
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let allowed: String = build_page_body(&root, Path::new(""), 0, repo_url, "ebb34e7", &Config::default(), &mut diagnostics);
+        assert!(allowed.contains("<div onclick=\"steal()\">Rule</div>"));
+        assert!(diagnostics.is_empty());
+
+        let config: Config = Config { html_policy: HtmlPolicy::Sanitize, ..Config::default() };
+        let sanitized: String = build_page_body(&root, Path::new(""), 0, repo_url, "ebb34e7", &config, &mut diagnostics);
+        assert!(sanitized.contains("<div>Rule</div>"));
+        assert!(sanitized.contains("Some <b>bold</b> text"));
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::new(Some("rules.md"), "stripped raw HTML: onclick on <div>")]
+        );
+
+        let config: Config = Config { html_policy: HtmlPolicy::Escape, ..Config::default() };
+        let escaped: String = build_page_body(&root, Path::new(""), 0, repo_url, "ebb34e7", &config, &mut Vec::new());
+        assert!(escaped.contains("&lt;div onclick=\"steal()\"&gt;Rule&lt;/div&gt;"));
+        assert!(escaped.contains("Some &lt;b&gt;bold&lt;/b&gt; text"));
+    }
+
+    #[test]
+    fn test_build_page_body_unsafe_urls() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Rules", "Rules");
+        node.depth = 1;
+        node.file_path = Some("rules.md".to_string());
+        node.content = Some("[Steal](javascript:alert(1)), ![Chart](JavaScript&colon;alert(2)) and [Site](https://acme.com)".to_string());
+        root.children.insert("Rules".to_string(), node);
+
+        let allowed: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &Config::default(), &mut Vec::new());
+        assert!(allowed.contains("<a href=\"javascript:alert(1)\">Steal</a>"));
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let config: Config = Config { html_policy: HtmlPolicy::Sanitize, ..Config::default() };
+        let sanitized: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &config, &mut diagnostics);
+        assert!(sanitized.contains("<a href=\"\">Steal</a>, <img src=\"\" alt=\"Chart\" /> and <a href=\"https://acme.com\">Site</a>"));
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(Some("rules.md"), "removed the unsafe URL `javascript:alert(1)`"),
+                Diagnostic::new(Some("rules.md"), "removed the unsafe URL `JavaScript:alert(2)`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_page_body_inner_headings() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Configuration", "Getting Started > Configuration");
+        node.depth = 2;
+        node.content = Some("# Setup\n\n## Options\n\n#### Deep".to_string());
+        let mut parent: DocNode = DocNode::new("Getting Started", "Getting Started");
+        parent.children.insert("Configuration".to_string(), node);
+        root.children.insert("Getting Started".to_string(), parent);
+
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &Config::default(), &mut Vec::new());
+
+        assert!(page_body.contains("<h3 id=\"getting-started-&gt;-configuration--setup\">Setup</h3>"));
+        assert!(page_body.contains("<h4 id=\"getting-started-&gt;-configuration--options\">Options</h4>"));
+        assert!(page_body.contains("<h6 id=\"getting-started-&gt;-configuration--deep\">Deep</h6>"));
+    }
+
+    #[test]
+    fn test_build_page_body_deep_nesting() {
+        let path: Vec<String> = (1..=7).map(|level| format!("Level {level}")).collect();
+        let mut node: DocNode = DocNode::new("Level 7", &path.join(" > "));
+        node.content = Some("## Inner".to_string());
+        for level in (1..=6).rev() {
+            let mut parent: DocNode = DocNode::new(&path[level - 1], &path[..level].join(" > "));
+            parent.children.insert(node.title.clone(), node);
+            node = parent;
+        }
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        root.children.insert(node.title.clone(), node);
+
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &Config::default(), &mut Vec::new());
+
+        assert!(page_body.contains("<h6 id=\"level-1->-level-2->-level-3->-level-4->-level-5->-level-6\"><span>Level 6</span></h6>"));
+        assert!(page_body.contains("<div role=\"heading\" aria-level=\"7\" class=\"deep-heading\" id=\"level-1->-level-2->-level-3->-level-4->-level-5->-level-6->-level-7\"><span>Level 7</span></div><div class=\"section-navigation depth-7\"><div class=\"breadcrumbs\""));
+        assert!(page_body.contains("<div role=\"heading\" aria-level=\"9\" class=\"deep-heading\" id=\"level-1-&gt;-level-2-&gt;-level-3-&gt;-level-4-&gt;-level-5-&gt;-level-6-&gt;-level-7--inner\">Inner</div>"));
+    }
+
+    #[test]
+    fn test_build_page_body_section_navigation() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+
+        let markdown_files: Vec<PathBuf> = find_markdown_files(test_dir.clone(), &["ignored_test_files".to_string()]);
+        let doc_structure: DocNode = build_doc_structure(&markdown_files, test_dir.as_path());
+        let page_body: String = build_page_body(&doc_structure, Path::new(""), 0, "", "ebb34e7", &Config::default(), &mut Vec::new());
+
+        assert!(page_body.contains("<div class=\"section-navigation depth-1\"><div class=\"sibling-links\"><a href=\"#features\" rel=\"next\">Features →</a></div></div>"));
+        assert!(page_body.contains("<div class=\"section-navigation depth-2\"><div class=\"breadcrumbs\" aria-label=\"Breadcrumb\"><a href=\"#domain\">Domain</a><span>›</span><span aria-current=\"location\">Subdomain two</span></div><div class=\"sibling-links\"><a href=\"#domain-&gt;-subdomain-one\" rel=\"prev\">← Subdomain one</a></div></div>"));
+    }
+
+    #[test]
+    fn test_build_page_body_diagrams() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Flows", "Flows");
+        node.content = Some("```mermaid\ngraph TD;\n    A-->B;\n```\n\n```dot\ndigraph { order -> invoice; }\n```".to_string());
+        root.children.insert("Flows".to_string(), node);
+
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &Config::default(), &mut Vec::new());
+        assert!(page_body.contains("<pre class=\"mermaid\">graph TD;\n    A--&gt;B;\n</pre>"));
+        assert!(!page_body.contains("<svg"));
+
+        let config: Config = Config { render_dot: true, ..Config::default() };
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &config, &mut Vec::new());
+        assert!(page_body.contains("<figure class=\"diagram\"><svg "));
+    }
+
+    #[test]
+    fn test_build_page_body_math() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Pricing", "Pricing");
+        node.file_path = Some("pricing.md".to_string());
+        node.content = Some("The price is $p \\times q$.\n\n$$\\begin{matrix} a \\end{matrix}$$".to_string());
+        root.children.insert("Pricing".to_string(), node);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &Config::default(), &mut diagnostics);
+
+        assert!(page_body.contains("The price is <math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"inline\"><semantics><mrow><mi>p</mi><mo>×</mo><mi>q</mi></mrow>"));
+        assert!(page_body.contains("<code class=\"math math-display\">$$\\begin{matrix} a \\end{matrix}$$</code>"));
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::new(Some("pricing.md"), "could not render math `\\begin{matrix} a \\end{matrix}`: unsupported command `\\begin`")]
+        );
+    }
+
+    #[test]
+    fn test_build_page_body_admonitions() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Pricing", "Pricing");
+        node.content = Some("> [!WARNING]\n> Prices are in cents.\n\n:::rule\n## Discounts\nThey never stack.\n:::".to_string());
+        root.children.insert("Pricing".to_string(), node);
+
+        let config: Config = Config { html_policy: HtmlPolicy::Escape, ..Config::default() };
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "", "ebb34e7", &config, &mut Vec::new());
+
+        assert!(page_body.contains("<aside class=\"admonition admonition-warning\" role=\"note\"><p class=\"admonition-title\">Warning</p>\n<p>Prices are in cents.</p>\n</aside>"));
+        assert!(page_body.contains("<aside class=\"admonition admonition-rule\" role=\"note\"><p class=\"admonition-title\">Rule</p>\n<h3 id=\"pricing--discounts\">Discounts</h3>\n<p>They never stack.</p>\n</aside>"));
+    }
+
+    #[test]
+    fn test_build_page_body_code_snippets() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Pricing", "Pricing");
+        node.content = Some("```rs source=src/pricing.rs lines=5-10\nfn main() {}\n```\n\n```python\nprint(1)\n```".to_string());
+        root.children.insert("Pricing".to_string(), node);
+
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "https://github.com/example/repo", "ebb34e7", &Config::default(), &mut Vec::new());
+
+        assert!(page_body.contains("<figcaption><a href=\"https://github.com/example/repo/blob/ebb34e7/src/pricing.rs?plain=1#L5-L10\" title=\"View on GitHub\">src/pricing.rs, lines 5-10</a></figcaption></figure>"));
+        assert!(page_body.contains("<figure class=\"code-snippet\"><pre style="));
+        assert_eq!(page_body.matches("<figure class=\"code-snippet\">").count(), 1);
+    }
+
+    #[test]
+    fn test_build_page_body_transclusion() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Privacy", "Privacy");
+        node.content = Some("<!-- crabodex:transclusion-start legal/gdpr.md 6-8 -->\n\nWe keep **nothing**.\n\n<!-- crabodex:transclusion-end -->".to_string());
+        root.children.insert("Privacy".to_string(), node);
+
+        let config: Config = Config { html_policy: HtmlPolicy::Escape, ..Config::default() };
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "https://github.com/example/repo", "ebb34e7", &config, &mut Vec::new());
+
+        assert!(page_body.contains("<div class=\"transclusion\"><p class=\"transclusion-source\">Included from <a href=\"https://github.com/example/repo/blob/ebb34e7/legal/gdpr.md?plain=1#L6-L8\" title=\"View on GitHub\">legal/gdpr.md</a></p>\n<p>We keep <strong>nothing</strong>.</p>\n</div>"));
+    }
+
+    #[test]
+    fn test_build_page_body_unpaired_transclusion_markers() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Privacy", "Privacy");
+        node.content = Some("<!-- crabodex:transclusion-end -->\n\nWe keep **nothing**.\n\n<!-- crabodex:transclusion-start legal/gdpr.md -->".to_string());
+        root.children.insert("Privacy".to_string(), node);
+
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "https://github.com/example/repo", "ebb34e7", &Config::default(), &mut Vec::new());
+
+        assert!(page_body.contains("<div class=\"depth-1\"><p>We keep <strong>nothing</strong>.</p>\n</div>"));
+        assert!(!page_body.contains("transclusion"));
+    }
+
+    #[test]
+    fn test_build_page_body_transclusion_images() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Flow", "Flow");
+        node.file_path = Some("file1.md".to_string());
+        node.content = Some("<!-- crabodex:transclusion-start sub_dir_2/file2.md -->\n\n![Flow](images/flow.svg)\n\n<!-- crabodex:transclusion-end -->\n\n![Flow](images/flow.svg)".to_string());
+        root.children.insert("Flow".to_string(), node);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let page_body: String = build_page_body(&root, &test_dir, 0, "", "", &Config::default(), &mut diagnostics);
+
+        // The images of the included file are found relative to it
+        assert!(page_body.contains("<img src=\"data:image/svg+xml;base64,PHN2Zy8+\" alt=\"Flow\" />"));
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>(),
+            ["file1.md: could not resolve image `images/flow.svg`: the file does not exist"]
+        );
+    }
+
+    #[test]
+    fn test_build_page_body_edit_links() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Pricing", "Pricing");
+        node.file_path = Some("docs/pricing.md".to_string());
+        root.children.insert("Pricing".to_string(), node);
+
+        let config: Config = Config { edit_branch: Some("master".to_string()), ..Config::default() };
+        let with_links: String = build_page_body(&root, Path::new(""), 0, "https://github.com/example/repo", "ebb34e7", &config, &mut Vec::new());
+        let config: Config = Config { edit_links: false, ..Config::default() };
+        let without_links: String = build_page_body(&root, Path::new(""), 0, "https://github.com/example/repo", "ebb34e7", &config, &mut Vec::new());
+
+        assert!(with_links.contains("<div class=\"section-navigation depth-1\"><a href=\"https://github.com/example/repo/edit/master/docs/pricing.md\" class=\"edit-link\" target=\"_blank\">Suggest an edit</a></div>"));
+        assert!(!without_links.contains("edit-link"));
+    }
+
+    #[test]
+    fn test_build_page_body_imported_repository() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Billing", "Billing");
+        node.file_path = Some("docs/billing.md".to_string());
+        node.content = Some("![Flow](../images/flow.png)".to_string());
+        node.repository = Some(Repository {
+            name: "billing".to_string(),
+            url: "https://gitlab.com/acme/billing".to_string(),
+            commit_hash: "1a2b3c4".to_string(),
+            source_ref: "1a2b3c4".to_string(),
+            forge: Forge::GitLab,
+            ..Repository::default()
+        });
+        root.children.insert("Billing".to_string(), node);
+
+        let config: Config = Config { edit_links: false, ..Config::default() };
+        let page_body: String = build_page_body(&root, Path::new(""), 0, "https://github.com/acme/portal", "ebb34e7", &config, &mut Vec::new());
 
-        assert!(page_body.contains("<h1 id=\"getting-started\"><span>Getting Started <a href=\"https://github.com/example/repo/blob/main/file1.md\" title=\"View on Github\" class=\"gh-icon\"></a></span></h1>"));
-        assert!(page_body.contains("<h2 id=\"getting-started->-configuration\"><span>Configuration <a href=\"https://github.com/example/repo/blob/main/sub_dir_2/file2.md\" title=\"View on Github\" class=\"gh-icon\"></a></span></h2>"));
+        // The imported section links to its own repository, in the format of its forge
+        assert!(page_body.contains("<a href=\"https://gitlab.com/acme/billing/-/blob/1a2b3c4/docs/billing.md\" title=\"View on GitLab\" class=\"gh-icon\"></a>"));
+        assert!(page_body.contains("<div class=\"section-navigation depth-1\"><a href=\"https://gitlab.com/acme/billing/-/commit/1a2b3c4\" class=\"commit-link\" target=\"_blank\">billing @ 1a2b3c4</a></div>"));
+        assert!(page_body.contains("<img src=\"https://gitlab.com/acme/billing/-/raw/1a2b3c4/images/flow.png\" alt=\"Flow\" />"));
     }
 }
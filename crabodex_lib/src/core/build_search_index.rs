@@ -0,0 +1,136 @@
+use pulldown_cmark::{Event, Parser, TagEnd};
+use serde::Serialize;
+
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_admonitions::expand_admonition_containers;
+use crate::DocNode;
+
+/// An entry of the search index, one per section of the documentation.
+/// - `id`: The anchor of the section in the HTML document.
+/// - `title`: The title of the section.
+/// - `path`: The titles of the parents of the section, separated by ` > `.
+/// - `text`: The plain text of the content of the section.
+/// - `tags`: The tags of the section.
+/// - `page`: The page of the section in a multi-page site, `None` in a single page.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchEntry {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub text: String,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<String>,
+}
+
+/// Builds the search index of the documentation, in display order.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+///
+/// # Returns
+/// The entries of the sections of the documentation.
+///
+#[must_use]
+pub fn build_search_index(root: &DocNode) -> Vec<SearchEntry> {
+    let mut entries: Vec<SearchEntry> = Vec::new();
+    collect_entries(root, &[], &mut entries);
+    entries
+}
+
+/// Serializes the search index so it can be embedded in a script of the HTML document.
+///
+/// # Arguments
+/// * `entries` - The entries of the search index.
+///
+/// # Returns
+/// The search index as a JSON array.
+///
+/// # Panics
+/// If the entries cannot be serialized.
+///
+#[must_use]
+pub fn search_index_json(entries: &[SearchEntry]) -> String {
+    // A `</script>` or a `<!--` in the text would close or break the script the index is embedded in
+    serde_json::to_string(entries)
+        .unwrap()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+/// Adds the entries of the children of a node, and of their own children, recursively.
+fn collect_entries(node: &DocNode, parents: &[&str], entries: &mut Vec<SearchEntry>) {
+    for child in node.sorted_children() {
+        entries.push(SearchEntry {
+            id: child.id(),
+            title: child.title.clone(),
+            path: parents.join(" > "),
+            text: child.content.as_deref().map(plain_text).unwrap_or_default(),
+            tags: child.tags.clone(),
+            page: None,
+        });
+
+        let mut child_parents: Vec<&str> = parents.to_vec();
+        child_parents.push(&child.title);
+        collect_entries(child, &child_parents, entries);
+    }
+}
+
+/// Extracts the text of a markdown content, without its markup and with its whitespace collapsed.
+fn plain_text(content: &str) -> String {
+    let content: String = expand_admonition_containers(content);
+    let mut text: String = String::with_capacity(content.len());
+
+    for event in Parser::new_ext(&content, markdown_options()) {
+        match event {
+            Event::Text(fragment) | Event::Code(fragment) => text.push_str(&fragment),
+            // The blocks are separated so their words are not glued together
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::CodeBlock | TagEnd::TableCell) => {
+                text.push(' ');
+            }
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_search_index() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Pricing", "Pricing");
+        node.content = Some("## Discounts\n\nThe **discounts** apply to `orders`.\n\n- First\n- Second".to_string());
+        let mut child: DocNode = DocNode::new("Refunds", "Pricing > Refunds");
+        child.tags = vec!["billing".to_string()];
+        node.children.insert("Refunds".to_string(), child);
+        root.children.insert("Pricing".to_string(), node);
+
+        let entries: Vec<SearchEntry> = build_search_index(&root);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].text, "Discounts The discounts apply to orders. First Second");
+        assert_eq!(entries[1].id, "pricing->-refunds");
+        assert_eq!(entries[1].path, "Pricing");
+        assert_eq!(entries[1].tags, vec!["billing"]);
+    }
+
+    #[test]
+    fn test_search_index_json() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Scripts", "Scripts");
+        node.content = Some("Never write `</script>` or `<!-- <script>` & co in a page.".to_string());
+        root.children.insert("Scripts".to_string(), node);
+
+        let json: String = search_index_json(&build_search_index(&root));
+
+        assert_eq!(json, r#"[{"id":"scripts","title":"Scripts","path":"","text":"Never write \u003c/script\u003e or \u003c!-- \u003cscript\u003e \u0026 co in a page.","tags":[]}]"#);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::core::escape_html::escape_html;
+use crate::DocNode;
+
+/// Builds the links to the previous and next siblings of a node.
+///
+/// # Arguments
+/// * `previous` - The sibling displayed before the node, if any.
+/// * `next` - The sibling displayed after the node, if any.
+///
+/// # Returns
+/// The links as an HTML string, empty if the node has no sibling.
+///
+#[must_use]
+pub fn build_sibling_links(previous: Option<&DocNode>, next: Option<&DocNode>) -> String {
+    if previous.is_none() && next.is_none() {
+        return String::default();
+    }
+
+    let mut html: String = String::from("<div class=\"sibling-links\">");
+    if let Some(previous) = previous {
+        html.push_str(&format!(
+            "<a href=\"#{}\" rel=\"prev\">← {}</a>",
+            escape_html(&previous.id()),
+            escape_html(&previous.title)
+        ));
+    }
+    if let Some(next) = next {
+        html.push_str(&format!(
+            "<a href=\"#{}\" rel=\"next\">{} →</a>",
+            escape_html(&next.id()),
+            escape_html(&next.title)
+        ));
+    }
+    html.push_str("</div>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_sibling_links() {
+        let previous: DocNode = DocNode::new("Taxes", "Pricing > Taxes");
+        let next: DocNode = DocNode::new("Refunds", "Pricing > Refunds");
+
+        assert_eq!(build_sibling_links(None, None), "");
+        assert_eq!(
+            build_sibling_links(Some(&previous), Some(&next)),
+            "<div class=\"sibling-links\"><a href=\"#pricing-&gt;-taxes\" rel=\"prev\">← Taxes</a><a href=\"#pricing-&gt;-refunds\" rel=\"next\">Refunds →</a></div>"
+        );
+    }
+}
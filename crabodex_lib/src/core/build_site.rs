@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::build_navigation::build_navigation;
+use crate::core::build_page_body::build_section;
+use crate::core::build_search_index::{build_search_index, search_index_json, SearchEntry};
+use crate::core::collect_headings::{collect_headings, slugify};
+use crate::core::config::Config;
+use crate::core::constants::{
+    MERMAID_DIAGRAM_START, SITE_MERMAID_SCRIPT_PATH, SITE_SCRIPT_PATH, SITE_SEARCH_INDEX_PATH, SITE_STYLE_PATH,
+};
+use crate::core::create_html_document::{create_html_document, DOCUMENT_SCRIPT, DOCUMENT_STYLE};
+use crate::core::diagnostic::Diagnostic;
+use crate::core::escape_html::{escape_html, unescape_html};
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// A page of a multi-page site.
+/// - `node`: The section of the page.
+/// - `depth`: The depth of the section in the documentation tree.
+/// - `siblings`: The previous and next siblings of the section, in display order.
+/// - `with_children`: Whether the subsections are on the page, or on pages of their own.
+/// - `file_name`: The name of the file of the page.
+///
+struct SitePage<'a> {
+    node: &'a DocNode,
+    depth: usize,
+    siblings: (Option<&'a DocNode>, Option<&'a DocNode>),
+    with_children: bool,
+    file_name: String,
+}
+
+/// Builds a multi-page site from the document structure.
+///
+/// The sections at the `site_page_depth` of the configuration get a page with their subsections,
+/// the shallower ones a page of their own. Every page has the whole navigation, and the links to
+/// the anchors of the other pages point at them. The root gets the `index.html` page.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `repository` - The repository the documentation comes from.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while building the pages are added to.
+///
+/// # Returns
+/// The files of the site relative to the output directory, with their content.
+///
+#[must_use]
+pub fn build_site(
+    root: &DocNode,
+    root_directory: &Path,
+    repository: &Repository,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut pages: Vec<SitePage> = Vec::new();
+    collect_pages(root, 0, config.site_page_depth.max(1), &mut pages);
+    let anchors: HashMap<String, String> = page_anchors(&pages);
+
+    let navigation: String = build_navigation(root, config);
+    let repository_link: (&str, &str) = (&repository.url, &repository.source_ref);
+    let mut files: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let mut has_diagrams: bool = false;
+
+    for page in &pages {
+        let page_body: String = if page.with_children {
+            build_section(page.node, root_directory, page.depth, page.siblings, repository_link, config, diagnostics)
+        } else {
+            let section: DocNode = DocNode { children: HashMap::new(), ..page.node.clone() };
+            build_section(&section, root_directory, page.depth, page.siblings, repository_link, config, diagnostics)
+        };
+        has_diagrams |= page_body.contains(MERMAID_DIAGRAM_START);
+        let html: String = site_document(&navigation, &page_body, &page.file_name, &anchors, repository, config);
+        files.push((PathBuf::from(&page.file_name), html.into_bytes()));
+    }
+
+    let index: String = site_document(&navigation, &index_body(root, &anchors), "index.html", &anchors, repository, config);
+    files.push((PathBuf::from("index.html"), index.into_bytes()));
+
+    // The results of the search open the page of their section
+    let search_index: Vec<SearchEntry> = build_search_index(root)
+        .into_iter()
+        .map(|entry| SearchEntry { page: anchors.get(&entry.id).cloned(), ..entry })
+        .collect();
+    files.push((
+        PathBuf::from(SITE_SEARCH_INDEX_PATH),
+        format!("window.crabodexSearchIndex = {};", search_index_json(&search_index)).into_bytes(),
+    ));
+    files.push((PathBuf::from(SITE_STYLE_PATH), DOCUMENT_STYLE.as_bytes().to_vec()));
+    files.push((PathBuf::from(SITE_SCRIPT_PATH), DOCUMENT_SCRIPT.as_bytes().to_vec()));
+    // The mermaid renderer is shared by the pages with diagrams instead of being embedded in each of them
+    if let Some(mermaid_script) = config.mermaid_script.as_ref().filter(|_| has_diagrams) {
+        files.push((PathBuf::from(SITE_MERMAID_SCRIPT_PATH), mermaid_script.as_bytes().to_vec()));
+    }
+
+    files
+}
+
+/// Lists the pages of the children of a node, and of their own children down to the page depth.
+fn collect_pages<'a>(node: &'a DocNode, depth: usize, page_depth: usize, pages: &mut Vec<SitePage<'a>>) {
+    let children: Vec<&DocNode> = node.sorted_children();
+    for (index, child) in children.iter().enumerate() {
+        let previous: Option<&DocNode> = index.checked_sub(1).map(|previous| children[previous]);
+        let next: Option<&DocNode> = children.get(index + 1).copied();
+        let with_children: bool = depth + 1 >= page_depth;
+
+        // The file names are unique, the sections with the same slug get a numbered suffix
+        let slug: String = slugify(&child.id());
+        let mut file_name: String = format!("{slug}.html");
+        let mut count: usize = 1;
+        while file_name == "index.html" || pages.iter().any(|page| page.file_name == file_name) {
+            count += 1;
+            file_name = format!("{slug}-{count}.html");
+        }
+
+        pages.push(SitePage { node: child, depth: depth + 1, siblings: (previous, next), with_children, file_name });
+        if !with_children {
+            collect_pages(child, depth + 1, page_depth, pages);
+        }
+    }
+}
+
+/// Maps the anchors of the sections and of their headings to the page they are on.
+fn page_anchors(pages: &[SitePage]) -> HashMap<String, String> {
+    let mut anchors: HashMap<String, String> = HashMap::new();
+
+    for page in pages {
+        let mut nodes: Vec<&DocNode> = vec![page.node];
+        while let Some(node) = nodes.pop() {
+            anchors.insert(node.id(), page.file_name.clone());
+            for heading in collect_headings(node) {
+                anchors.insert(heading.id, page.file_name.clone());
+            }
+            if page.with_children {
+                nodes.extend(node.children.values());
+            }
+        }
+    }
+
+    anchors
+}
+
+/// Builds the body of the index page: the list of the top-level sections.
+fn index_body(root: &DocNode, anchors: &HashMap<String, String>) -> String {
+    let mut html: String = String::from("<div class=\"depth-1\"><ul>");
+    for child in root.sorted_children() {
+        let id: String = child.id();
+        html.push_str(&format!(
+            "<li><a href=\"{}#{}\">{}</a></li>",
+            escape_html(anchors.get(&id).map(String::as_str).unwrap_or_default()),
+            escape_html(&id),
+            escape_html(&child.title)
+        ));
+    }
+    html.push_str("</ul></div>");
+    html
+}
+
+/// Creates the HTML document of a page of the site, with its links to the other pages.
+fn site_document(
+    navigation: &str,
+    page_body: &str,
+    file_name: &str,
+    anchors: &HashMap<String, String>,
+    repository: &Repository,
+    config: &Config,
+) -> String {
+    create_html_document(
+        &rewrite_anchor_links(navigation, file_name, anchors),
+        &rewrite_anchor_links(page_body, file_name, anchors),
+        "",
+        &repository.name,
+        &repository.description,
+        &repository.commit_hash,
+        &repository.url,
+        config,
+    )
+}
+
+/// Points the links to the anchors of the other pages at these pages.
+///
+/// The anchors are escaped in most links, but not in the navigation.
+///
+/// # Arguments
+/// * `html` - The HTML of a page.
+/// * `file_name` - The name of the file of the page.
+/// * `anchors` - The name of the file of the page each anchor is on.
+///
+/// # Returns
+/// The HTML with the links to the other pages prefixed with their file name.
+///
+#[must_use]
+pub fn rewrite_anchor_links(html: &str, file_name: &str, anchors: &HashMap<String, String>) -> String {
+    let mut rewritten: String = String::with_capacity(html.len());
+    let mut rest: &str = html;
+
+    while let Some(index) = rest.find("href=\"#") {
+        let (before, after) = rest.split_at(index + "href=\"".len());
+        rewritten.push_str(before);
+
+        let anchor_end: usize = after.find('"').unwrap_or(after.len());
+        let id: String = unescape_html(&after[1..anchor_end]);
+        if let Some(page) = anchors.get(&id).filter(|page| page.as_str() != file_name) {
+            rewritten.push_str(page);
+        }
+        rest = after;
+    }
+
+    rewritten.push_str(rest);
+    rewritten
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::config::OutputFormat;
+
+    use super::*;
+
+    #[test]
+    fn test_build_site() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        pricing.content = Some("See the [refunds](#refunds).\n\n## Discounts".to_string());
+        let mut discounts: DocNode = DocNode::new("Discounts", "Pricing > Discounts");
+        discounts.content = Some("Some discounts.".to_string());
+        pricing.children.insert("Discounts".to_string(), discounts);
+        let mut refunds: DocNode = DocNode::new("Refunds", "Refunds");
+        refunds.content = Some("Back to the [discounts](#pricing->-discounts).".to_string());
+        root.children.insert("Pricing".to_string(), pricing);
+        root.children.insert("Refunds".to_string(), refunds);
+
+        let config: Config = Config { format: OutputFormat::Site, ..Config::default() };
+        let files: Vec<(PathBuf, Vec<u8>)> = build_site(&root, Path::new(""), &Repository::default(), &config, &mut Vec::new());
+        let file_names: Vec<String> = files.iter().map(|(path, _)| path.to_string_lossy().to_string()).collect();
+        assert_eq!(
+            file_names,
+            vec!["pricing.html", "refunds.html", "index.html", SITE_SEARCH_INDEX_PATH, SITE_STYLE_PATH, SITE_SCRIPT_PATH]
+        );
+
+        let refunds_page: String = String::from_utf8(files[1].1.clone()).unwrap();
+        assert!(refunds_page.contains("pricing.html#pricing-"));
+        assert!(refunds_page.contains(SITE_STYLE_PATH));
+
+        let search_index: String = String::from_utf8(files[3].1.clone()).unwrap();
+        assert!(search_index.contains("\"id\":\"pricing-\\u003e-discounts\",\"title\":\"Discounts\",\"path\":\"Pricing\",\"text\":\"Some discounts.\",\"tags\":[],\"page\":\"pricing.html\""));
+    }
+
+    #[test]
+    fn test_build_site_mermaid_script() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        pricing.content = Some("```mermaid\ngraph TD;\n```".to_string());
+        root.children.insert("Pricing".to_string(), pricing);
+        root.children.insert("Refunds".to_string(), DocNode::new("Refunds", "Refunds"));
+
+        let config: Config = Config {
+            format: OutputFormat::Site,
+            mermaid_script: Some("window.mermaid = {};".to_string()),
+            ..Config::default()
+        };
+        let files: Vec<(PathBuf, Vec<u8>)> = build_site(&root, Path::new(""), &Repository::default(), &config, &mut Vec::new());
+
+        let mermaid_file: &(PathBuf, Vec<u8>) = files.last().unwrap();
+        assert_eq!(mermaid_file.0, PathBuf::from(SITE_MERMAID_SCRIPT_PATH));
+        assert_eq!(mermaid_file.1, b"window.mermaid = {};");
+        let pricing_page: String = String::from_utf8(files[0].1.clone()).unwrap();
+        assert!(pricing_page.contains(&format!("<script src=\"{SITE_MERMAID_SCRIPT_PATH}\"></script>")));
+        assert!(!pricing_page.contains("window.mermaid"));
+        let refunds_page: String = String::from_utf8(files[1].1.clone()).unwrap();
+        assert!(!refunds_page.contains(SITE_MERMAID_SCRIPT_PATH));
+    }
+
+    #[test]
+    fn test_rewrite_anchor_links() {
+        let anchors: HashMap<String, String> = HashMap::from([
+            ("pricing".to_string(), "pricing.html".to_string()),
+            ("pricing->-discounts".to_string(), "pricing.html".to_string()),
+            ("refunds".to_string(), "refunds.html".to_string()),
+        ]);
+        let html: &str = "<a href=\"#pricing->-discounts\">A</a><a href=\"#pricing-&gt;-discounts\">B</a><a href=\"#refunds\">C</a><a href=\"#unknown\">D</a>";
+
+        assert_eq!(
+            rewrite_anchor_links(html, "refunds.html", &anchors),
+            "<a href=\"pricing.html#pricing->-discounts\">A</a><a href=\"pricing.html#pricing-&gt;-discounts\">B</a><a href=\"#refunds\">C</a><a href=\"#unknown\">D</a>"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use crate::core::config::Config;
+
+/// Builds the link to a file of the repository, in the format of the forge or the custom one.
+///
+/// # Arguments
+/// * `repo_url` - The URL of the repository.
+/// * `source_ref` - The branch, tag or commit to link to.
+/// * `file_path` - The path of the file in the repository.
+/// * `lines` - The first and last lines to point at in the file, if any.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// The URL of the file, or `None` if the ref to link to is unknown.
+///
+#[must_use]
+pub fn build_source_link(
+    repo_url: &str,
+    source_ref: &str,
+    file_path: &str,
+    lines: Option<(usize, usize)>,
+    config: &Config,
+) -> Option<String> {
+    if is_source_ref_missing(repo_url, source_ref, config) {
+        return None;
+    }
+
+    let source_link: String = source_link_template(config)
+        .replace("{repo_url}", repo_url.trim_end_matches('/'))
+        .replace("{ref_kind}", ref_kind(source_ref, config))
+        .replace("{ref}", source_ref)
+        .replace("{file_path}", file_path);
+
+    match lines {
+        Some((start, end)) => {
+            let fragment: String = config
+                .line_fragment_template
+                .as_deref()
+                .unwrap_or(config.forge.line_fragment_template())
+                .replace("{start}", &start.to_string())
+                .replace("{end}", &end.to_string());
+            Some(format!("{source_link}{fragment}"))
+        }
+        None => Some(source_link),
+    }
+}
+
+/// Checks whether the source links need a ref to point at, and none is known.
+///
+/// # Arguments
+/// * `repo_url` - The URL of the repository.
+/// * `source_ref` - The branch, tag or commit to link to, empty if it is unknown.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// `true` if the source links are left out for lack of a ref.
+///
+#[must_use]
+pub fn is_source_ref_missing(repo_url: &str, source_ref: &str, config: &Config) -> bool {
+    !repo_url.is_empty() && source_ref.is_empty() && source_link_template(config).contains("{ref")
+}
+
+/// The format of the source links: the custom one, or the one of the forge.
+fn source_link_template(config: &Config) -> &str {
+    config.source_link_template.as_deref().unwrap_or(config.forge.source_link_template())
+}
+
+/// The title of the links to the source files: the forge they open, unless their format is custom.
+///
+/// # Arguments
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// The title of the links.
+///
+#[must_use]
+pub fn source_link_title(config: &Config) -> String {
+    match config.source_link_template {
+        Some(_) => "View the source".to_string(),
+        None => format!("View on {}", config.forge.title()),
+    }
+}
+
+/// The kind of a ref, for the forges whose paths depend on it: `tag`, `commit` or `branch`.
+///
+/// The tags are the ones of the repository, the commits are the hexadecimal hashes, and the other refs are branches.
+fn ref_kind(source_ref: &str, config: &Config) -> &'static str {
+    if config.git_tags.iter().any(|tag| tag == source_ref) {
+        "tag"
+    } else if (7..=64).contains(&source_ref.len()) && source_ref.chars().all(|c| c.is_ascii_hexdigit()) {
+        "commit"
+    } else {
+        "branch"
+    }
+}
+
+/// Builds the link to the raw content of a file of the repository, in the format of the forge.
+///
+/// # Arguments
+/// * `repo_url` - The URL of the repository.
+/// * `source_ref` - The branch, tag or commit to link to.
+/// * `file_path` - The path of the file in the repository.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// The URL of the raw file.
+///
+#[must_use]
+pub fn build_raw_link(repo_url: &str, source_ref: &str, file_path: &str, config: &Config) -> String {
+    config
+        .forge
+        .raw_link_template()
+        .replace("{repo_url}", repo_url.trim_end_matches('/'))
+        .replace("{ref_kind}", ref_kind(source_ref, config))
+        .replace("{ref}", source_ref)
+        .replace("{file_path}", file_path)
+}
+
+/// Builds the link opening a file of the repository in the web editor of the forge.
+///
+/// # Arguments
+/// * `repo_url` - The URL of the repository.
+/// * `file_path` - The path of the file in the repository.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// The URL of the editor, or `None` if the edit links are turned off, the repository URL is unknown,
+/// or the branch to edit the files on is unknown.
+///
+#[must_use]
+pub fn build_edit_link(repo_url: &str, file_path: &str, config: &Config) -> Option<String> {
+    if !config.edit_links || repo_url.is_empty() || is_edit_branch_missing(config) {
+        return None;
+    }
+
+    let edit_link: String = edit_link_template(config)
+        .replace("{repo_url}", repo_url.trim_end_matches('/'))
+        .replace("{branch}", config.edit_branch.as_deref().unwrap_or_default())
+        .replace("{file_path}", file_path);
+    Some(edit_link)
+}
+
+/// Checks whether the edit links need a branch to open the files on, and none is configured.
+///
+/// # Arguments
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// `true` if the edit links are left out for lack of a branch.
+///
+#[must_use]
+pub fn is_edit_branch_missing(config: &Config) -> bool {
+    config.edit_links && config.edit_branch.is_none() && edit_link_template(config).contains("{branch}")
+}
+
+/// The format of the edit links: the custom one, or the one of the forge.
+fn edit_link_template(config: &Config) -> &str {
+    config.edit_link_template.as_deref().unwrap_or(config.forge.edit_link_template())
+}
+
+/// Builds the link to a commit of the repository, in the format of the forge.
+///
+/// # Arguments
+/// * `repo_url` - The URL of the repository.
+/// * `commit_hash` - The hash of the commit.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// The URL of the commit.
+///
+#[must_use]
+pub fn build_commit_link(repo_url: &str, commit_hash: &str, config: &Config) -> String {
+    config
+        .forge
+        .commit_link_template()
+        .replace("{repo_url}", repo_url.trim_end_matches('/'))
+        .replace("{commit_hash}", commit_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Forge;
+
+    #[test]
+    fn test_build_source_link() {
+        let gitlab: Config = Config { forge: Forge::GitLab, ..Config::default() };
+        let custom: Config = Config {
+            source_link_template: Some("{repo_url}/browse/{file_path}?at={ref}".to_string()),
+            line_fragment_template: Some("#{start}-{end}".to_string()),
+            ..Config::default()
+        };
+
+        assert_eq!(
+            build_source_link("https://github.com/example/repo", "master", "docs/a.md", None, &Config::default()).as_deref(),
+            Some("https://github.com/example/repo/blob/master/docs/a.md")
+        );
+        assert_eq!(
+            build_source_link("https://github.com/example/repo", "master", "docs/a.md", Some((12, 40)), &Config::default()).as_deref(),
+            Some("https://github.com/example/repo/blob/master/docs/a.md?plain=1#L12-L40")
+        );
+        assert_eq!(
+            build_source_link("https://gitlab.com/example/repo/", "ebb34e7", "docs/a.md", Some((12, 40)), &gitlab).as_deref(),
+            Some("https://gitlab.com/example/repo/-/blob/ebb34e7/docs/a.md?plain=1#L12-40")
+        );
+        assert_eq!(
+            build_source_link("https://git.example.com/projects/repo", "v1.0", "docs/a.md", Some((12, 40)), &custom).as_deref(),
+            Some("https://git.example.com/projects/repo/browse/docs/a.md?at=v1.0#12-40")
+        );
+        // Without a ref, the links are left out, unless their format does not need one
+        assert_eq!(build_source_link("https://github.com/example/repo", "", "docs/a.md", None, &Config::default()), None);
+        let without_ref: Config = Config { source_link_template: Some("{repo_url}/{file_path}".to_string()), ..Config::default() };
+        assert_eq!(
+            build_source_link("https://git.example.com/repo", "", "docs/a.md", None, &without_ref).as_deref(),
+            Some("https://git.example.com/repo/docs/a.md")
+        );
+    }
+
+    #[test]
+    fn test_build_source_link_gitea() {
+        let gitea: Config = Config { forge: Forge::Gitea, git_tags: vec!["v1.0".to_string()], ..Config::default() };
+        let repo_url: &str = "https://codeberg.org/example/repo";
+
+        assert_eq!(build_source_link(repo_url, "main", "docs/a.md", None, &gitea).as_deref(), Some("https://codeberg.org/example/repo/src/branch/main/docs/a.md"));
+        assert_eq!(build_source_link(repo_url, "v1.0", "docs/a.md", None, &gitea).as_deref(), Some("https://codeberg.org/example/repo/src/tag/v1.0/docs/a.md"));
+        assert_eq!(
+            build_source_link(repo_url, "ebb34e7", "docs/a.md", Some((12, 40)), &gitea).as_deref(),
+            Some("https://codeberg.org/example/repo/src/commit/ebb34e7/docs/a.md?display=source#L12-L40")
+        );
+        assert_eq!(build_raw_link(repo_url, "v1.0", "images/a.png", &gitea), "https://codeberg.org/example/repo/raw/tag/v1.0/images/a.png");
+        assert_eq!(source_link_title(&gitea), "View on Gitea");
+    }
+
+    #[test]
+    fn test_build_edit_link() {
+        let gitlab: Config = Config { forge: Forge::GitLab, edit_branch: Some("develop".to_string()), ..Config::default() };
+        let off: Config = Config { edit_links: false, ..Config::default() };
+        let custom: Config = Config { edit_link_template: Some("{repo_url}/edit/{file_path}".to_string()), ..Config::default() };
+
+        // Without a branch, there is no file to open in the editor
+        assert_eq!(build_edit_link("https://github.com/example/repo", "docs/a.md", &Config::default()), None);
+        assert!(is_edit_branch_missing(&Config::default()));
+        assert_eq!(
+            build_edit_link("https://git.example.com/repo", "docs/a.md", &custom),
+            Some("https://git.example.com/repo/edit/docs/a.md".to_string())
+        );
+        assert_eq!(
+            build_edit_link("https://gitlab.com/example/repo", "docs/a.md", &gitlab),
+            Some("https://gitlab.com/example/repo/-/edit/develop/docs/a.md".to_string())
+        );
+        assert_eq!(build_edit_link("https://github.com/example/repo", "docs/a.md", &off), None);
+        assert_eq!(build_edit_link("", "docs/a.md", &gitlab), None);
+    }
+}
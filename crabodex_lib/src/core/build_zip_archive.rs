@@ -0,0 +1,108 @@
+/// Builds a ZIP archive of files, stored without compression.
+///
+/// The files keep their order in the archive, which some formats rely on: the first file of an
+/// EPUB container must be its uncompressed `mimetype`.
+///
+/// # Arguments
+/// * `files` - The paths of the files in the archive, with their content.
+///
+/// # Returns
+/// The content of the archive.
+///
+/// # Panics
+/// If the archive is larger than 4 GiB, or has more than 65535 files.
+///
+#[must_use]
+pub fn build_zip_archive(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut archive: Vec<u8> = Vec::new();
+    let mut central_directory: Vec<u8> = Vec::new();
+
+    for (name, content) in files {
+        let offset: u32 = u32::try_from(archive.len()).unwrap();
+        let header: Vec<u8> = file_header(name, content);
+
+        // The local header of the file, followed by its content
+        archive.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(content);
+
+        // Its entry in the central directory, pointing at the local header
+        central_directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+        central_directory.extend_from_slice(&20_u16.to_le_bytes());
+        central_directory.extend_from_slice(&header);
+        central_directory.extend_from_slice(&[0; 6]);
+        central_directory.extend_from_slice(&0_u32.to_le_bytes());
+        central_directory.extend_from_slice(&offset.to_le_bytes());
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+
+    let entries: u16 = u16::try_from(files.len()).unwrap();
+    let central_directory_offset: u32 = u32::try_from(archive.len()).unwrap();
+    let central_directory_size: u32 = u32::try_from(central_directory.len()).unwrap();
+    archive.extend_from_slice(&central_directory);
+
+    // The end of the central directory record
+    archive.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&entries.to_le_bytes());
+    archive.extend_from_slice(&entries.to_le_bytes());
+    archive.extend_from_slice(&central_directory_size.to_le_bytes());
+    archive.extend_from_slice(&central_directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0_u16.to_le_bytes());
+
+    archive
+}
+
+/// The fields shared by the local header of a file and its entry in the central directory.
+fn file_header(name: &str, content: &[u8]) -> Vec<u8> {
+    let size: u32 = u32::try_from(content.len()).unwrap();
+    let mut header: Vec<u8> = Vec::with_capacity(26);
+    // The version needed to extract, no flags, no compression, and the 1980-01-01 00:00 date
+    header.extend_from_slice(&20_u16.to_le_bytes());
+    header.extend_from_slice(&0_u16.to_le_bytes());
+    header.extend_from_slice(&0_u16.to_le_bytes());
+    header.extend_from_slice(&0_u16.to_le_bytes());
+    header.extend_from_slice(&0x0021_u16.to_le_bytes());
+    header.extend_from_slice(&crc32(content).to_le_bytes());
+    header.extend_from_slice(&size.to_le_bytes());
+    header.extend_from_slice(&size.to_le_bytes());
+    header.extend_from_slice(&u16::try_from(name.len()).unwrap().to_le_bytes());
+    header.extend_from_slice(&0_u16.to_le_bytes());
+    header
+}
+
+/// The CRC-32 checksum of some data, as used by the ZIP format.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_zip_archive() {
+        let files: Vec<(String, Vec<u8>)> = vec![
+            ("mimetype".to_string(), b"application/epub+zip".to_vec()),
+            ("check.txt".to_string(), b"123456789".to_vec()),
+        ];
+
+        let archive: Vec<u8> = build_zip_archive(&files);
+
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(&archive[..4], b"PK\x03\x04");
+        assert_eq!(&archive[30..38], b"mimetype");
+        assert_eq!(&archive[38..58], b"application/epub+zip");
+        // The end of the central directory lists the two files
+        assert_eq!(&archive[archive.len() - 22..archive.len() - 18], b"PK\x05\x06");
+        assert_eq!(&archive[archive.len() - 12..archive.len() - 10], &[2, 0]);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::core::diagnostic::Diagnostic;
+use crate::DocNode;
+
+/// Checks the depth of the document structure recursively.
+///
+/// Every node deeper than the maximum depth raises a warning with its path.
+///
+/// # Arguments
+/// * `node` - The current node in the document structure.
+/// * `max_depth` - The maximum depth allowed.
+/// * `diagnostics` - The list the warnings are added to.
+///
+pub fn check_depth(node: &DocNode, max_depth: usize, diagnostics: &mut Vec<Diagnostic>) {
+    let mut children: Vec<&DocNode> = node.children.values().collect();
+    children.sort_by(|a, b| a.path.cmp(&b.path));
+
+    for child in children {
+        let depth: usize = child.path.split(" > ").count();
+        if depth > max_depth {
+            diagnostics.push(Diagnostic::new(
+                child.file_path.as_deref(),
+                &format!(
+                    "path `{}` is {} levels deep, deeper than the maximum of {}",
+                    child.path, depth, max_depth
+                ),
+            ));
+        }
+        check_depth(child, max_depth, diagnostics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_depth() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut parent: DocNode = DocNode::new("A", "A");
+        let mut child: DocNode = DocNode::new("B", "A > B");
+        child.file_path = Some("a/b.md".to_string());
+        child.children.insert("C".to_string(), DocNode::new("C", "A > B > C"));
+        parent.children.insert("B".to_string(), child);
+        root.children.insert("A".to_string(), parent);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        check_depth(&root, 1, &mut diagnostics);
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(Some("a/b.md"), "path `A > B` is 2 levels deep, deeper than the maximum of 1"),
+                Diagnostic::new(None, "path `A > B > C` is 3 levels deep, deeper than the maximum of 1"),
+            ]
+        );
+    }
+}
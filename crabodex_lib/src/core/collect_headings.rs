@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_admonitions::expand_admonition_containers;
+use crate::DocNode;
+
+/// A heading found in the markdown content of a node.
+/// - `level`: The level of the heading in the markdown content, from 1 to 6.
+/// - `title`: The text of the heading.
+/// - `id`: The anchor of the heading, unique in the whole document.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: usize,
+    pub title: String,
+    pub id: String,
+}
+
+/// Collects the headings of the markdown content of a node, in order.
+///
+/// The anchors are prefixed with the id of the node so they are unique in
+/// the whole document. Headings with the same title in a node get a numbered
+/// suffix. An explicit `{#id}` attribute on the heading is kept as is.
+///
+/// # Arguments
+/// * `node` - The node whose content is scanned.
+///
+/// # Returns
+/// The headings of the content, in the order they appear.
+///
+#[must_use]
+pub fn collect_headings(node: &DocNode) -> Vec<Heading> {
+    let Some(content) = &node.content else {
+        return Vec::new();
+    };
+
+    let node_id: String = node.id();
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut current: Option<(usize, Option<String>, String)> = None;
+
+    let content: String = expand_admonition_containers(content);
+    for event in Parser::new_ext(&content, markdown_options()) {
+        match event {
+            Event::Start(Tag::Heading { level, id, .. }) => {
+                current = Some((level as usize, id.map(|id| id.to_string()), String::default()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, title)) = &mut current {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, id, title)) = current.take() {
+                    let id: String = id.unwrap_or_else(|| {
+                        let base: String = format!("{}--{}", node_id, slugify(&title));
+                        let count: &mut usize = seen.entry(base.clone()).or_insert(0);
+                        *count += 1;
+                        if *count == 1 { base } else { format!("{base}-{count}") }
+                    });
+                    headings.push(Heading { level, title, id });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    headings
+}
+
+/// Turns a heading title into an anchor: lowercase alphanumerics separated by dashes.
+#[must_use]
+pub fn slugify(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_headings() {
+        let mut node: DocNode = DocNode::new("Configuration", "Getting Started > Configuration");
+        node.content = Some("Intro\n\n## Install the `cli`\n\n### Options\n\n## Options\n\n### Options\n\n## Custom {#custom-anchor}".to_string());
+
+        let headings: Vec<Heading> = collect_headings(&node);
+
+        let ids: Vec<&str> = headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "getting-started->-configuration--install-the-cli",
+                "getting-started->-configuration--options",
+                "getting-started->-configuration--options-2",
+                "getting-started->-configuration--options-3",
+                "custom-anchor",
+            ]
+        );
+        assert_eq!(headings[0].title, "Install the cli");
+        assert_eq!(headings[1].level, 3);
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::constants::{
+    DEFAULT_ASSETS_DIRECTORY, DEFAULT_MAN_SECTION, DEFAULT_MAX_DEPTH, DEFAULT_MAX_IMAGE_SIZE,
+    DEFAULT_OUTPUT_DIRECTORY, DEFAULT_SITE_PAGE_DEPTH, DEFAULT_TOC_HEADING_LEVEL,
+};
+
+/// The configuration of the documentation generation.
+/// - `format`: The format of the generated documentation.
+/// - `output_directory`: The directory the formats made of several files are written to, and the page links its copied images from.
+/// - `site_page_depth`: The depth of the sections that get a page of their own in the `Site` format,
+///   with their subsections. The shallower sections get a page without them.
+/// - `man_path`: The path of the section exported as a man page with its subsections, the whole documentation if not set.
+/// - `man_section`: The section of the manual the man page belongs to, `1` for the user commands.
+/// - `html_policy`: What to do with the raw HTML found in the markdown content.
+/// - `toc_heading_level`: The deepest level of the markdown headings listed in the navigation, 0 to list none.
+/// - `max_depth`: The maximum depth of the documentation tree, deeper paths raise a warning.
+/// - `mermaid_script`: The mermaid renderer script embedded in the page when it has mermaid diagrams.
+/// - `render_dot`: Whether the `dot` code blocks are rendered to SVG at build time.
+/// - `image_mode`: What to do with the local images referenced in the markdown content.
+/// - `max_image_size`: The size limit of the embedded images in bytes, larger ones are kept as links.
+/// - `assets_directory`: The directory the images are copied to in the `Copy` image mode, relative to the output directory.
+/// - `source_ref`: The branch, tag or commit the source links point at, the commit hash if not set. Empty if it is unknown, the source links are then left out.
+/// - `forge`: The forge hosting the repository, which gives the format of the links to it.
+/// - `git_tags`: The tags of the repository, to tell them from the branches in the links of the forges whose paths depend on it.
+/// - `source_link_template`: A custom format of the source links, instead of the one of the forge.
+///   `{repo_url}`, `{ref}`, `{ref_kind}` and `{file_path}` are replaced in it.
+/// - `line_fragment_template`: A custom format of the end of the source links pointing at some lines,
+///   instead of the one of the forge. `{start}` and `{end}` are replaced in it.
+/// - `edit_links`: Whether the sections link to their file in the web editor of the forge.
+/// - `edit_branch`: The branch the edit links open the files on, the edit links are left out if not set.
+/// - `edit_link_template`: A custom format of the edit links, instead of the one of the forge.
+///   `{repo_url}`, `{branch}` and `{file_path}` are replaced in it.
+///
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub format: OutputFormat,
+    pub output_directory: PathBuf,
+    pub site_page_depth: usize,
+    pub man_path: Option<String>,
+    pub man_section: ManSection,
+    pub html_policy: HtmlPolicy,
+    pub toc_heading_level: usize,
+    pub max_depth: usize,
+    pub mermaid_script: Option<String>,
+    pub render_dot: bool,
+    pub image_mode: ImageMode,
+    pub max_image_size: usize,
+    pub assets_directory: PathBuf,
+    pub source_ref: Option<String>,
+    pub forge: Forge,
+    pub git_tags: Vec<String>,
+    pub source_link_template: Option<String>,
+    pub line_fragment_template: Option<String>,
+    pub edit_links: bool,
+    pub edit_branch: Option<String>,
+    pub edit_link_template: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            format: OutputFormat::default(),
+            output_directory: PathBuf::from(DEFAULT_OUTPUT_DIRECTORY),
+            site_page_depth: DEFAULT_SITE_PAGE_DEPTH,
+            man_path: None,
+            man_section: ManSection::default(),
+            html_policy: HtmlPolicy::default(),
+            toc_heading_level: DEFAULT_TOC_HEADING_LEVEL,
+            max_depth: DEFAULT_MAX_DEPTH,
+            mermaid_script: None,
+            render_dot: false,
+            image_mode: ImageMode::default(),
+            max_image_size: DEFAULT_MAX_IMAGE_SIZE,
+            assets_directory: PathBuf::from(DEFAULT_ASSETS_DIRECTORY),
+            source_ref: None,
+            forge: Forge::default(),
+            git_tags: Vec::new(),
+            source_link_template: None,
+            line_fragment_template: None,
+            edit_links: true,
+            edit_branch: None,
+            edit_link_template: None,
+        }
+    }
+}
+
+/// The format of the generated documentation.
+/// - `Html`: A single HTML page.
+/// - `Json`: The documentation tree as JSON, following the published schema.
+/// - `Site`: One HTML page per section, written to the output directory with their shared assets.
+/// - `Markdown`: A single markdown document, with the sections concatenated in display order.
+/// - `Llms`: An `llms.txt` index and a full plain-text rendition, written to the output directory.
+/// - `Epub`: An EPUB 3 book with a chapter per top-level section, written to the output directory.
+/// - `Confluence`: A Confluence storage-format page per section and their manifest, written to the output directory.
+/// - `Latex`: A LaTeX document with a title page and a table of contents, ready to be typeset.
+/// - `Man`: A roff man page of a section and its subsections.
+/// - `Xml`: The documentation tree as XML, following the published XML Schema.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Html,
+    Json,
+    Site,
+    Markdown,
+    Llms,
+    Epub,
+    Confluence,
+    Latex,
+    Man,
+    Xml,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "html" => Ok(OutputFormat::Html),
+            "json" => Ok(OutputFormat::Json),
+            "site" => Ok(OutputFormat::Site),
+            "markdown" => Ok(OutputFormat::Markdown),
+            "llms" => Ok(OutputFormat::Llms),
+            "epub" => Ok(OutputFormat::Epub),
+            "confluence" => Ok(OutputFormat::Confluence),
+            "latex" => Ok(OutputFormat::Latex),
+            "man" => Ok(OutputFormat::Man),
+            "xml" => Ok(OutputFormat::Xml),
+            _ => Err(format!(
+                "Unknown format `{value}`, expected `html`, `json`, `site`, `markdown`, `llms`, `epub`, `confluence`, `latex`, `man` or `xml`"
+            )),
+        }
+    }
+}
+
+/// The policy applied to the raw HTML found in the markdown content.
+/// - `Allow`: The raw HTML is passed through as is.
+/// - `Sanitize`: Only a safe allowlist of tags and attributes is kept.
+/// - `Escape`: The raw HTML is escaped and displayed as text.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HtmlPolicy {
+    #[default]
+    Allow,
+    Sanitize,
+    Escape,
+}
+
+impl FromStr for HtmlPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "allow" => Ok(HtmlPolicy::Allow),
+            "sanitize" | "sanitise" => Ok(HtmlPolicy::Sanitize),
+            "escape" => Ok(HtmlPolicy::Escape),
+            _ => Err(format!("Unknown HTML policy `{value}`, expected `allow`, `sanitize` or `escape`")),
+        }
+    }
+}
+
+/// The section of the manual a man page belongs to: `1` to `9`, optionally followed by a suffix like the `p` of `3p`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManSection(String);
+
+impl Default for ManSection {
+    fn default() -> Self {
+        ManSection(DEFAULT_MAN_SECTION.to_string())
+    }
+}
+
+impl FromStr for ManSection {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let is_number: bool = value.starts_with(|c: char| ('1'..='9').contains(&c));
+        // The suffix starts with a letter, so `42` is not read as the section `4`
+        let suffix: &str = value.get(1..).unwrap_or_default();
+        let is_suffix: bool = suffix.is_empty()
+            || (suffix.starts_with(|c: char| c.is_ascii_lowercase())
+                && suffix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+        if is_number && is_suffix {
+            Ok(ManSection(value.to_string()))
+        } else {
+            Err(format!("Unknown man section `{value}`, expected `1` to `9`, optionally followed by a suffix like `3p`"))
+        }
+    }
+}
+
+impl fmt::Display for ManSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What to do with the local images referenced in the markdown content.
+/// - `Embed`: The images are embedded in the page as data URIs.
+/// - `Copy`: The images are copied to the assets directory, and the page links to the copies. Only for the `Site` format, the others embed them.
+/// - `Keep`: The links to the images are kept as written.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageMode {
+    #[default]
+    Embed,
+    Copy,
+    Keep,
+}
+
+impl FromStr for ImageMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "embed" => Ok(ImageMode::Embed),
+            "copy" => Ok(ImageMode::Copy),
+            "keep" => Ok(ImageMode::Keep),
+            _ => Err(format!("Unknown image mode `{value}`, expected `embed`, `copy` or `keep`")),
+        }
+    }
+}
+
+/// The forge hosting the repository.
+/// - `GitHub`: github.com or GitHub Enterprise.
+/// - `GitLab`: gitlab.com or a self-hosted GitLab.
+/// - `Bitbucket`: bitbucket.org.
+/// - `Gitea`: A Gitea or Forgejo instance.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    #[default]
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+}
+
+impl Forge {
+    /// The name of the forge, as displayed in the titles of the links to it.
+    #[must_use]
+    pub fn title(self) -> &'static str {
+        match self {
+            Forge::GitHub => "GitHub",
+            Forge::GitLab => "GitLab",
+            Forge::Bitbucket => "Bitbucket",
+            Forge::Gitea => "Gitea",
+        }
+    }
+
+    /// The format of the links to a file of the repository at a ref.
+    ///
+    /// `{ref_kind}` is `branch`, `tag` or `commit`, for the forges whose paths depend on it.
+    #[must_use]
+    pub fn source_link_template(self) -> &'static str {
+        match self {
+            Forge::GitHub => "{repo_url}/blob/{ref}/{file_path}",
+            Forge::GitLab => "{repo_url}/-/blob/{ref}/{file_path}",
+            Forge::Bitbucket => "{repo_url}/src/{ref}/{file_path}",
+            Forge::Gitea => "{repo_url}/src/{ref_kind}/{ref}/{file_path}",
+        }
+    }
+
+    /// The format of the links to the raw content of a file of the repository at a ref.
+    #[must_use]
+    pub fn raw_link_template(self) -> &'static str {
+        match self {
+            Forge::GitHub | Forge::Bitbucket => "{repo_url}/raw/{ref}/{file_path}",
+            Forge::GitLab => "{repo_url}/-/raw/{ref}/{file_path}",
+            Forge::Gitea => "{repo_url}/raw/{ref_kind}/{ref}/{file_path}",
+        }
+    }
+
+    /// The format of the end of the links to a file pointing at some of its lines.
+    ///
+    /// The markdown files are rendered by the forges, the line anchors only exist in their source view.
+    #[must_use]
+    pub fn line_fragment_template(self) -> &'static str {
+        match self {
+            Forge::GitHub => "?plain=1#L{start}-L{end}",
+            Forge::GitLab => "?plain=1#L{start}-{end}",
+            Forge::Bitbucket => "#lines-{start}:{end}",
+            Forge::Gitea => "?display=source#L{start}-L{end}",
+        }
+    }
+
+    /// The format of the links opening a file of the repository in the web editor.
+    #[must_use]
+    pub fn edit_link_template(self) -> &'static str {
+        match self {
+            Forge::GitHub => "{repo_url}/edit/{branch}/{file_path}",
+            Forge::GitLab => "{repo_url}/-/edit/{branch}/{file_path}",
+            Forge::Bitbucket => "{repo_url}/src/{branch}/{file_path}?mode=edit",
+            Forge::Gitea => "{repo_url}/_edit/{branch}/{file_path}",
+        }
+    }
+
+    /// The format of the links to a commit of the repository.
+    #[must_use]
+    pub fn commit_link_template(self) -> &'static str {
+        match self {
+            Forge::GitHub | Forge::Gitea => "{repo_url}/commit/{commit_hash}",
+            Forge::GitLab => "{repo_url}/-/commit/{commit_hash}",
+            Forge::Bitbucket => "{repo_url}/commits/{commit_hash}",
+        }
+    }
+}
+
+impl FromStr for Forge {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "github" => Ok(Forge::GitHub),
+            "gitlab" => Ok(Forge::GitLab),
+            "bitbucket" => Ok(Forge::Bitbucket),
+            "gitea" | "forgejo" => Ok(Forge::Gitea),
+            _ => Err(format!("Unknown forge `{value}`, expected `github`, `gitlab`, `bitbucket` or `gitea`")),
+        }
+    }
+}
+
+impl fmt::Display for Forge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name: &str = match self {
+            Forge::GitHub => "github",
+            Forge::GitLab => "gitlab",
+            Forge::Bitbucket => "bitbucket",
+            Forge::Gitea => "gitea",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_man_section() {
+        assert_eq!("1".parse::<ManSection>().map(|section| section.to_string()), Ok("1".to_string()));
+        assert_eq!("3p".parse::<ManSection>().map(|section| section.to_string()), Ok("3p".to_string()));
+        assert_eq!("1ssl".parse::<ManSection>().map(|section| section.to_string()), Ok("1ssl".to_string()));
+        assert!("0".parse::<ManSection>().is_err());
+        assert!("42".parse::<ManSection>().is_err());
+        assert!("".parse::<ManSection>().is_err());
+        assert!("3P".parse::<ManSection>().is_err());
+        assert!("user".parse::<ManSection>().is_err());
+    }
+}
@@ -1,2 +1,71 @@
 /// The default Front Matter prefix.
 pub const DEFAULT_FRONT_MATTER_PREFIX: &str = "---";
+
+/// The deepest level of the markdown headings listed in the navigation by default.
+pub const DEFAULT_TOC_HEADING_LEVEL: usize = 3;
+
+/// The deepest heading level HTML has, deeper sections are rendered as styled elements.
+pub const MAX_HTML_HEADING_LEVEL: usize = 6;
+
+/// The maximum depth of the documentation tree by default, deeper paths raise a warning.
+pub const DEFAULT_MAX_DEPTH: usize = 6;
+
+/// The maximum number of nested markdown inclusions, deeper ones raise a warning.
+pub const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// The size limit of the images embedded in the page by default, in bytes.
+pub const DEFAULT_MAX_IMAGE_SIZE: usize = 1024 * 1024;
+
+/// The directory the images are copied to by default when they are not embedded.
+pub const DEFAULT_ASSETS_DIRECTORY: &str = "assets";
+
+/// The version of the schema of the JSON export, increased on breaking changes.
+pub const JSON_SCHEMA_VERSION: usize = 1;
+
+/// The published JSON Schema of the JSON export.
+pub const JSON_SCHEMA_URL: &str = "https://raw.githubusercontent.com/fabien-h/crabodex/main/documentation/schema/doc-tree.v1.schema.json";
+
+/// The directory the outputs made of several files are written to by default.
+pub const DEFAULT_OUTPUT_DIRECTORY: &str = "crabodex";
+
+/// The depth of the documentation tree whose sections get a page of their own in a multi-page site by default.
+pub const DEFAULT_SITE_PAGE_DEPTH: usize = 1;
+
+/// The style shared by the pages of a multi-page site, relative to the output directory.
+pub const SITE_STYLE_PATH: &str = "assets/crabodex.css";
+
+/// The script shared by the pages of a multi-page site, relative to the output directory.
+pub const SITE_SCRIPT_PATH: &str = "assets/crabodex.js";
+
+/// The search index shared by the pages of a multi-page site, relative to the output directory.
+pub const SITE_SEARCH_INDEX_PATH: &str = "assets/search-index.js";
+
+/// The mermaid renderer shared by the pages of a multi-page site with diagrams, relative to the output directory.
+pub const SITE_MERMAID_SCRIPT_PATH: &str = "assets/mermaid.js";
+
+/// The `llms.txt` index of the documentation, relative to the output directory.
+pub const LLMS_INDEX_PATH: &str = "llms.txt";
+
+/// The full plain-text rendition of the documentation, relative to the output directory.
+pub const LLMS_FULL_TEXT_PATH: &str = "llms-full.txt";
+
+/// The language of the EPUB books, required by the format.
+pub const EPUB_LANGUAGE: &str = "en";
+
+/// The manifest of the pages exported for Confluence, relative to the output directory.
+pub const CONFLUENCE_MANIFEST_PATH: &str = "manifest.json";
+
+/// The section of the man pages by default, the one of the user commands.
+pub const DEFAULT_MAN_SECTION: &str = "1";
+
+/// The version of the schema of the XML export, increased on breaking changes.
+pub const XML_SCHEMA_VERSION: usize = 1;
+
+/// The namespace of the elements of the XML export.
+pub const XML_NAMESPACE: &str = "https://github.com/fabien-h/crabodex/doc-tree/v1";
+
+/// The published XML Schema of the XML export.
+pub const XML_SCHEMA_URL: &str = "https://raw.githubusercontent.com/fabien-h/crabodex/main/documentation/schema/doc-tree.v1.xsd";
+
+/// The opening tag of the container of a mermaid diagram, drawn in the browser by the mermaid renderer.
+pub const MERMAID_DIAGRAM_START: &str = "<pre class=\"mermaid\">";
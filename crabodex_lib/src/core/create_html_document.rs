@@ -1,16 +1,960 @@
 use chrono::Local;
 use html_minifier::HTMLMinifier;
 
+use crate::core::build_source_link::build_commit_link;
+use crate::core::config::{Config, OutputFormat};
+use crate::core::constants::{
+    MERMAID_DIAGRAM_START, SITE_MERMAID_SCRIPT_PATH, SITE_SCRIPT_PATH, SITE_SEARCH_INDEX_PATH, SITE_STYLE_PATH,
+};
+
+/// The style of the HTML documents.
+pub const DOCUMENT_STYLE: &str = r#"
+*,
+*::before,
+*::after {
+    box-sizing: border-box;
+    margin: 0;
+    padding: 0;
+    font: inherit;
+}
+
+html {
+    font-size: 62.5%;
+}
+
+:root {
+    --bg-color: #ffffff;
+    --text-color: #333333;
+    --nav-bg-color: #f4f4f4;
+    --pre-bg-color: #f4f4f4;
+    --primary: #18181b;
+    --muted-foreground: #71717a;
+    --note-color: #0969da;
+    --tip-color: #1a7f37;
+    --important-color: #8250df;
+    --warning-color: #9a6700;
+    --caution-color: #cf222e;
+    --rule-color: #0550ae;
+    --deprecated-color: #6e7781;
+}
+
+.dark-mode {
+    --bg-color: #2d2d2d;
+    --text-color: #ffffff;
+    --nav-bg-color: #222222;
+    --pre-bg-color: #444444;
+    --primary: #fafafa;
+    --muted-foreground: #d4d4d8;
+    --note-color: #4493f8;
+    --tip-color: #3fb950;
+    --important-color: #ab7df8;
+    --warning-color: #d29922;
+    --caution-color: #f85149;
+    --rule-color: #79c0ff;
+    --deprecated-color: #9198a1;
+}
+
+body {
+    line-height: 1.75;
+    color: #333;
+    height: 100%;
+    -webkit-font-smoothing: antialiased;
+    font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple Color Emoji", "Segoe UI Emoji", "Segoe UI Symbol";
+    font-size: 1.6rem;
+    color: var(--text-color);
+    background-color: var(--bg-color);
+}
+
+a {
+    color: var(--muted-foreground);
+}
+
+a:hover {
+    color: var(--primary);
+}
+
+.container {
+    display: flex;
+    height: 100%;
+}
+
+button {
+    cursor: pointer;
+}
+
+nav {
+    width: 250px;
+    height: 100vh;
+    position: fixed;
+    left: 0;
+    top: 0;
+    transition: transform 0.3s ease-in-out;
+    border-right: 1px solid #ddd;
+    z-index: 1000;
+    background-color: var(--bg-color);
+    display: flex;
+    flex-direction: column;
+}
+
+nav>header {
+    border-bottom: 1px solid #ddd;
+    flex-grow: 0;
+    flex-shrink: 0;
+}
+
+nav>header>.repo-name {
+    font-family: Bahnschrift, 'DIN Alternate', 'Franklin Gothic Medium', 'Nimbus Sans Narrow', sans-serif-condensed, sans-serif;
+    font-size: 2rem;
+    padding: 10px 15px 0 15px;
+}
+
+nav>header>.repo-description {
+    padding: 0 15px 10px 15px;
+    font-size: 1.4rem;
+    border-bottom: 1px solid #ddd;
+    color: #999;
+}
+
+nav>header>.commit-hash {
+    padding: 10px 15px 0 15px;
+    font-size: 1.4rem;
+}
+
+nav>header>.commit-hash>a {
+    text-decoration: underline;
+}
+
+nav>header>.generation-date {
+    padding: 0 15px 10px 15px;
+    font-size: 1.4rem;
+}
+
+nav>header>.generation-date>span:first-of-type {
+    font-size: 1.4rem;
+    color: #999;
+}
+
+nav>header>.generation-date>span:last-of-type {
+    font-size: 1.4rem;
+    font-weight: bold;
+}
+
+nav>ul {
+    overflow-y: auto;
+    font-size: 1.4rem;
+    padding: 10px 15px 10px 20px;
+    flex-grow: 1;
+}
+
+nav a {
+    display: block;
+    text-decoration: none;
+    color: var(--text-color);
+}
+
+nav a:hover {
+    text-decoration: underline;
+}
+
+nav ul {
+    list-style-type: "→";
+    margin-bottom: 10px;
+}
+
+nav ul li {
+    padding-left: 5px;
+}
+
+nav ul ul {
+    margin-left: 10px;
+    padding: 0;
+}
+
+main {
+    flex-grow: 1;
+    margin-left: 250px;
+    overflow-y: auto;
+    position: relative;
+    overflow-x: hidden;
+    background-color: var(--bg-color);
+}
+
+main>header {
+    border-bottom: 1px solid #ddd;
+    height: 40px;
+    display: flex;
+    align-items: center;
+    justify-content: flex-end;
+    margin-bottom: 20px;
+}
+
+main>footer {
+    margin-top: 30px;
+    border-top: 1px solid #ddd;
+}
+
+main>footer>p {
+    max-width: 800px;
+    margin: 0 auto;
+    padding: 10px 15px 100px 15px;
+}
+
+main>div {
+    padding: 10px;
+    width: 100%;
+    max-width: 800px;
+    margin: 0 auto;
+}
+
+main p {
+    margin-bottom: 20px;
+}
+
+.section-navigation {
+    display: flex;
+    justify-content: space-between;
+    gap: 15px;
+    width: 100%;
+    max-width: 800px;
+    margin: 0 auto;
+    padding: 0 10px 10px 10px;
+    font-size: 1.4rem;
+    color: var(--muted-foreground);
+}
+
+.depth-2 {
+    padding-left: 20px;
+}
+
+.depth-3 {
+    padding-left: 30px;
+}
+
+.depth-4 {
+    padding-left: 40px;
+}
+
+.depth-5 {
+    padding-left: 50px;
+}
+
+.depth-6 {
+    padding-left: 60px;
+}
+
+.depth-7 {
+    padding-left: 70px;
+}
+
+.depth-8 {
+    padding-left: 80px;
+}
+
+.depth-9 {
+    padding-left: 90px;
+}
+
+.depth-10 {
+    padding-left: 100px;
+}
+
+h1,
+h2,
+h3,
+h4,
+h5,
+h6,
+.deep-heading {
+    padding: 10px;
+    font-family: Bahnschrift, 'DIN Alternate', 'Franklin Gothic Medium', 'Nimbus Sans Narrow', sans-serif-condensed, sans-serif;
+    width: 100%;
+    max-width: 800px;
+    margin: 0 auto;
+}
+
+h1>span,
+h2>span,
+h3>span,
+h4>span,
+h5>span,
+h6>span,
+.deep-heading>span {
+    display: flex;
+    align-items: center;
+    justify-content: space-between;
+}
+
+h1:after,
+h2:after,
+h3:after,
+h4:after,
+h5:after,
+h6:after,
+.deep-heading:after {
+    display: block;
+    width: 100%;
+    height: 1px;
+    background-color: #ddd;
+    content: '';
+}
+
+h1 {
+    font-size: 3.2rem;
+}
+
+h2 {
+    font-size: 2.8rem;
+    padding-left: 20px;
+}
+
+h3 {
+    font-size: 2.4rem;
+    padding-left: 30px;
+}
+
+h4 {
+    font-size: 2.2rem;
+    padding-left: 40px;
+}
+
+h5 {
+    font-size: 2rem;
+    padding-left: 50px;
+}
+
+h6 {
+    font-size: 1.8rem;
+    padding-left: 60px;
+}
+
+.deep-heading {
+    font-size: 1.6rem;
+    font-weight: bold;
+    padding-left: 70px;
+}
+
+.breadcrumbs>span {
+    padding: 0 5px;
+}
+
+.sibling-links {
+    display: flex;
+    gap: 15px;
+    margin-left: auto;
+}
+
+.edit-link {
+    margin-left: auto;
+    white-space: nowrap;
+}
+
+.commit-link {
+    margin-left: auto;
+    white-space: nowrap;
+}
+
+.sibling-links + .edit-link,
+.sibling-links + .commit-link,
+.commit-link + .edit-link {
+    margin-left: 0;
+}
+
+pre {
+    background-color: #f4f4f4;
+    padding: 10px;
+    overflow-x: auto;
+}
+
+#menu-toggle {
+    position: absolute;
+    top: 0;
+    right: -51px;
+    z-index: 1000;
+    color: #333;
+    border: none;
+    cursor: pointer;
+    border-bottom-right-radius: 5px;
+    border-right: 1px solid #ddd;
+    border-bottom: 1px solid #ddd;
+    background-color: #fff;
+    width: 50px;
+    text-align: center;
+    font-size: 24px;
+    height: 50px;
+    display: none;
+}
+
+#search {
+    position: relative;
+    height: 100%;
+    display: flex;
+    align-items: center;
+    padding: 0 10px;
+    margin-right: auto;
+}
+
+#search-input {
+    width: 250px;
+    padding: 2px 8px;
+    font-size: 1.4rem;
+    color: var(--text-color);
+    background-color: var(--bg-color);
+    border: 1px solid #ddd;
+    border-radius: 4px;
+}
+
+#search-results {
+    position: absolute;
+    top: 100%;
+    left: 10px;
+    width: 450px;
+    max-width: calc(100vw - 20px);
+    max-height: 60vh;
+    overflow-y: auto;
+    list-style-type: none;
+    padding: 0;
+    margin: 0;
+    font-size: 1.4rem;
+    background-color: var(--bg-color);
+    border: 1px solid #ddd;
+    z-index: 900;
+}
+
+#search-results:empty {
+    display: none;
+}
+
+#search-results li {
+    margin: 0;
+    border-bottom: 1px solid #ddd;
+}
+
+#search-results a {
+    display: block;
+    padding: 5px 10px;
+    text-decoration: none;
+    color: var(--text-color);
+}
+
+#search-results a:hover,
+#search-results a.selected {
+    background-color: #66666622;
+}
+
+#search-results .search-result-path,
+#search-results .search-result-text {
+    display: block;
+    color: var(--muted-foreground);
+    font-size: 1.2rem;
+}
+
+#search-results .search-result-title {
+    font-weight: 700;
+}
+
+mark {
+    background-color: #ffe066;
+    color: #333;
+}
+
+#print-btn>svg,
+#mode-toggle>svg {
+    display: block;
+    width: 18px;
+    fill: var(--text-color);
+}
+
+#decrease-font,
+#increase-font,
+#top-repo-link,
+#print-btn,
+#mode-toggle {
+    color: var(--text-color);
+    cursor: pointer;
+    background: none;
+    border: none;
+    font-weight: bold;
+    height: 100%;
+    border-left: 1px solid #ddd;
+    padding: 0 8px;
+    font-size: 16px;
+    display: flex;
+    align-items: center;
+}
+
+@media (max-width:768px) {
+    nav {
+        transform: translateX(-100%);
+    }
+
+    nav.active {
+        transform: translateX(0);
+    }
+
+    main {
+        margin-left: 0;
+    }
+
+    #menu-toggle {
+        display: block;
+    }
+}
+
+.gh-icon {
+    mask-image: url("data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 24 24'%3E%3Cpath d='M12,2A10,10 0 0,0 2,12C2,16.42 4.87,20.17 8.84,21.5C9.34,21.58 9.5,21.27 9.5,21C9.5,20.77 9.5,20.14 9.5,19.31C6.73,19.91 6.14,17.97 6.14,17.97C5.68,16.81 5.03,16.5 5.03,16.5C4.12,15.88 5.1,15.9 5.1,15.9C6.1,15.97 6.63,16.93 6.63,16.93C7.5,18.45 8.97,18 9.54,17.76C9.63,17.11 9.89,16.67 10.17,16.42C7.95,16.17 5.62,15.31 5.62,11.5C5.62,10.39 6,9.5 6.65,8.79C6.55,8.54 6.2,7.5 6.75,6.15C6.75,6.15 7.59,5.88 9.5,7.17C10.29,6.95 11.15,6.84 12,6.84C12.85,6.84 13.71,6.95 14.5,7.17C16.41,5.88 17.25,6.15 17.25,6.15C17.8,7.5 17.45,8.54 17.35,8.79C18,9.5 18.38,10.39 18.38,11.5C18.38,15.32 16.04,16.16 13.81,16.41C14.17,16.72 14.5,17.33 14.5,18.26C14.5,19.6 14.5,20.68 14.5,21C14.5,21.27 14.66,21.59 15.17,21.5C19.14,20.16 22,16.42 22,12A10,10 0 0,0 12,2Z'/%3E%3C/svg%3E");
+    background-color: var(--text-color);
+    background-size: contain;
+    width: 20px;
+    height: 20px;
+    display: block;
+    flex-shrink: 0;
+    background-repeat: no-repeat;
+    background-position: center;
+}
+
+code,
+pre {
+    font-family: ui-monospace, 'Cascadia Code', 'Source Code Pro', Menlo, Consolas, 'DejaVu Sans Mono', monospace;
+    background-color: #f4f4f4;
+    border-radius: 4px;
+    color: #333;
+    border: 1px solid #ddd;
+}
+
+pre code {
+    background: none;
+    border: none;
+}
+
+code {
+    padding: 2px 4px;
+    font-size: 90%;
+    text-wrap: wrap;
+}
+
+pre {
+    padding: 10px;
+    overflow-x: auto;
+}
+
+pre code {
+    background-color: transparent;
+    padding: 0;
+}
+
+math[display="block"] {
+    margin-bottom: 20px;
+}
+
+.diagram {
+    margin-bottom: 20px;
+    text-align: center;
+}
+
+.diagram svg {
+    max-width: 100%;
+    height: auto;
+}
+
+.code-snippet {
+    margin-bottom: 20px;
+}
+
+.code-snippet pre {
+    margin-bottom: 0;
+}
+
+.code-snippet figcaption {
+    font-size: 80%;
+    text-align: right;
+}
+
+.transclusion {
+    border-left: 3px dashed #999;
+    padding-left: 15px;
+    margin-bottom: 20px;
+}
+
+.transclusion-source {
+    font-size: 80%;
+    color: var(--muted-foreground);
+}
+
+blockquote {
+    border-left: 3px solid #999;
+    padding: 5px 5px 5px 15px;
+    background: #66666611;
+    margin-bottom: 20px;
+}
+
+.admonition {
+    --admonition-color: var(--note-color);
+    border-left: 4px solid var(--admonition-color);
+    padding: 5px 5px 5px 15px;
+    background: #66666611;
+    margin-bottom: 20px;
+}
+
+.admonition-title {
+    color: var(--admonition-color);
+    font-weight: 700;
+}
+
+.admonition-tip {
+    --admonition-color: var(--tip-color);
+}
+
+.admonition-important {
+    --admonition-color: var(--important-color);
+}
+
+.admonition-warning {
+    --admonition-color: var(--warning-color);
+}
+
+.admonition-caution {
+    --admonition-color: var(--caution-color);
+}
+
+.admonition-rule {
+    --admonition-color: var(--rule-color);
+}
+
+.admonition-deprecated {
+    --admonition-color: var(--deprecated-color);
+}
+
+table {
+    border-collapse: collapse;
+    width: 100%;
+    margin-bottom: 10px;
+}
+
+th,
+td {
+    border: 1px solid #ddd;
+    padding: 8px;
+    text-align: left;
+}
+
+th {
+    background-color: #00000022;
+    font-weight: bold;
+}
+
+tr:nth-child(even) {
+    background-color: #00000011;
+}
+
+ul,
+ol {
+    padding-left: 20px;
+    margin-bottom: 20px;
+}
+
+ul {
+    list-style-type: disc;
+}
+
+ol {
+    list-style-type: decimal;
+}
+
+li {
+    margin-bottom: 5px;
+}
+
+hr {
+    border: none;
+    border-top: 1px solid #ddd;
+    margin: 20px 0;
+}
+
+strong {
+    font-weight: 700;
+}
+
+em {
+    font-style: italic;
+}
+
+@media print {
+    main>header {
+        display: none;
+    }
+
+    body {
+        font-size: 12pt;
+    }
+
+    #menu-toggle{
+       display: none;
+    }
+
+    .section-navigation {
+        display: none;
+    }
+
+    .admonition {
+        break-inside: avoid;
+        -webkit-print-color-adjust: exact;
+        print-color-adjust: exact;
+    }
+
+    nav {
+        width: 100%;
+        height: auto;
+        position: relative;
+        transform: translateX(0);
+        border-right: none;
+        display: block;
+    }
+"#;
+
+/// The script of the HTML documents: the font size, the menu, the print button, the theme and the search.
+pub const DOCUMENT_SCRIPT: &str = r#"
+document.addEventListener('DOMContentLoaded', () => {
+
+    const html = document.documentElement;
+
+    const getCurrentFontSize = () => parseFloat(window.getComputedStyle(html).fontSize);
+    const setFontSize = (newSize) => html.style.fontSize = newSize + 'px';
+    document.getElementById('increase-font').addEventListener('click', () => {
+        const currentSize = getCurrentFontSize();
+        setFontSize(Math.min(currentSize + 2, 24));
+    });
+    document.getElementById('decrease-font').addEventListener('click', () => {
+        const currentSize = getCurrentFontSize();
+        setFontSize(Math.max(currentSize - 2, 8));
+    });
+
+    document.getElementById('menu-toggle').addEventListener('click', () => {
+        document.querySelector('nav').classList.toggle('active');
+    });
+    window.addEventListener('resize', () => {
+        document.querySelector('nav').classList.remove('active');
+    });
+
+    const printBtn = document.getElementById('print-btn');
+    printBtn.addEventListener('click', () => {
+        window.print();
+    });
+
+    const setTheme = (isDark) => {
+        document.body.classList.toggle('dark-mode', isDark);
+        localStorage.setItem('storedTheme', isDark ? 'dark' : 'light')
+    };
+    document.getElementById('mode-toggle').addEventListener('click', () => {
+        setTheme(!document.body.classList.contains('dark-mode'))
+    });
+    const storedTheme = localStorage.getItem('storedTheme');
+    if (storedTheme) {
+        setTheme(storedTheme === 'dark');
+    } else {
+        setTheme(window.matchMedia('(prefers-color-scheme: dark)').matches);
+    }
+
+    const searchIndexElement = document.getElementById('search-index');
+    const searchIndex = searchIndexElement ? JSON.parse(searchIndexElement.textContent) : window.crabodexSearchIndex;
+    const searchInput = document.getElementById('search-input');
+    const searchResults = document.getElementById('search-results');
+    const escapeHtml = (text) => text.split('&').join('&amp;').split('<').join('&lt;').split('>').join('&gt;')
+        .split('"').join('&quot;').split("'").join('&#39;');
+
+    /* The ranges of a text matching the terms, sorted and without overlaps */
+    const findMatches = (text, terms) => {
+        const lowerText = text.toLowerCase();
+        const matches = [];
+        terms.forEach((term) => {
+            let index = lowerText.indexOf(term);
+            while (index !== -1) {
+                matches.push([index, index + term.length]);
+                index = lowerText.indexOf(term, index + term.length);
+            }
+        });
+        matches.sort((a, b) => a[0] - b[0]);
+        return matches.reduce((kept, match) => {
+            if (kept.length === 0 || match[0] >= kept[kept.length - 1][1]) {
+                kept.push(match);
+            }
+            return kept;
+        }, []);
+    };
+    const highlight = (text, terms) => {
+        let html = '';
+        let last = 0;
+        findMatches(text, terms).forEach(([start, end]) => {
+            html += escapeHtml(text.slice(last, start)) + '<mark>' + escapeHtml(text.slice(start, end)) + '</mark>';
+            last = end;
+        });
+        return html + escapeHtml(text.slice(last));
+    };
+    const snippet = (text, terms) => {
+        const matches = findMatches(text, terms);
+        if (matches.length === 0) {
+            return text.slice(0, 120);
+        }
+        const start = Math.max(matches[0][0] - 40, 0);
+        const end = Math.min(matches[0][0] + 120, text.length);
+        return (start > 0 ? '…' : '') + text.slice(start, end) + (end < text.length ? '…' : '');
+    };
+
+    /* Every term has to match, the ones in the title and the tags weigh more than the ones in the text */
+    const scoreEntry = (entry, terms) => {
+        let score = 0;
+        for (const term of terms) {
+            const title = entry.title.toLowerCase();
+            let termScore = 0;
+            if (title.includes(term)) {
+                termScore += title.startsWith(term) ? 15 : 10;
+            }
+            if (entry.tags.some((tag) => tag.toLowerCase().includes(term))) {
+                termScore += 6;
+            }
+            if (entry.path.toLowerCase().includes(term)) {
+                termScore += 3;
+            }
+            termScore += Math.min(findMatches(entry.text, [term]).length, 5);
+            if (termScore === 0) {
+                return 0;
+            }
+            score += termScore;
+        }
+        return score;
+    };
+
+    const clearPageHighlights = () => {
+        document.querySelectorAll('mark.search-highlight').forEach((mark) => {
+            const parent = mark.parentNode;
+            parent.replaceChild(document.createTextNode(mark.textContent), mark);
+            parent.normalize();
+        });
+    };
+    /* The content of a section follows its heading and its navigation */
+    const highlightSection = (id, terms) => {
+        clearPageHighlights();
+        const heading = document.getElementById(id);
+        let content = heading ? heading.nextElementSibling : null;
+        if (content && content.classList.contains('section-navigation')) {
+            content = content.nextElementSibling;
+        }
+        if (!content || content.tagName !== 'DIV' || content.classList.contains('deep-heading')) {
+            return;
+        }
+        const walker = document.createTreeWalker(content, NodeFilter.SHOW_TEXT);
+        const textNodes = [];
+        while (walker.nextNode()) {
+            if (!walker.currentNode.parentNode.closest('svg, math, script, style')) {
+                textNodes.push(walker.currentNode);
+            }
+        }
+        textNodes.forEach((node) => {
+            const text = node.textContent;
+            const matches = findMatches(text, terms);
+            if (matches.length === 0) {
+                return;
+            }
+            const fragment = document.createDocumentFragment();
+            let last = 0;
+            matches.forEach(([start, end]) => {
+                fragment.appendChild(document.createTextNode(text.slice(last, start)));
+                const mark = document.createElement('mark');
+                mark.className = 'search-highlight';
+                mark.textContent = text.slice(start, end);
+                fragment.appendChild(mark);
+                last = end;
+            });
+            fragment.appendChild(document.createTextNode(text.slice(last)));
+            node.parentNode.replaceChild(fragment, node);
+        });
+    };
+
+    const searchTerms = () => searchInput.value.toLowerCase().split(' ').filter((term) => term.length > 0);
+    const closeSearch = () => {
+        searchResults.innerHTML = '';
+    };
+    const goToResult = (link) => {
+        const terms = searchTerms();
+        const id = link.dataset.id;
+        if (!document.getElementById(id)) {
+            window.location.href = link.href;
+            return;
+        }
+        window.location.hash = id;
+        highlightSection(id, terms);
+        closeSearch();
+    };
+    searchInput.addEventListener('input', () => {
+        const terms = searchTerms();
+        if (terms.length === 0) {
+            closeSearch();
+            return;
+        }
+        const results = searchIndex
+            .map((entry) => [scoreEntry(entry, terms), entry])
+            .filter(([score]) => score > 0)
+            .sort((a, b) => b[0] - a[0])
+            .slice(0, 20);
+        searchResults.innerHTML = results.map(([, entry]) =>
+            '<li><a href="' + escapeHtml(entry.page || '') + '#' + escapeHtml(entry.id) + '" data-id="' + escapeHtml(entry.id) + '">'
+            + '<span class="search-result-title">' + highlight(entry.title, terms) + '</span>'
+            + (entry.path ? '<span class="search-result-path">' + highlight(entry.path, terms) + '</span>' : '')
+            + '<span class="search-result-text">' + highlight(snippet(entry.text, terms), terms) + '</span>'
+            + '</a></li>'
+        ).join('');
+        const first = searchResults.querySelector('a');
+        if (first) {
+            first.classList.add('selected');
+        }
+    });
+    searchResults.addEventListener('click', (event) => {
+        const link = event.target.closest('a');
+        if (link) {
+            event.preventDefault();
+            goToResult(link);
+        }
+    });
+    searchInput.addEventListener('keydown', (event) => {
+        const links = Array.from(searchResults.querySelectorAll('a'));
+        const selected = links.findIndex((link) => link.classList.contains('selected'));
+        if (event.key === 'Enter' && selected !== -1) {
+            goToResult(links[selected]);
+        } else if (event.key === 'Escape') {
+            closeSearch();
+            clearPageHighlights();
+        } else if ((event.key === 'ArrowDown' || event.key === 'ArrowUp') && links.length > 0) {
+            event.preventDefault();
+            const next = (selected + (event.key === 'ArrowDown' ? 1 : links.length - 1)) % links.length;
+            links.forEach((link, index) => link.classList.toggle('selected', index === next));
+            links[next].scrollIntoView({ block: 'nearest' });
+        }
+    });
+    document.addEventListener('click', (event) => {
+        if (!event.target.closest('#search')) {
+            closeSearch();
+        }
+    });
+    document.addEventListener('keydown', (event) => {
+        if (event.key === '/' && document.activeElement !== searchInput) {
+            event.preventDefault();
+            searchInput.focus();
+        }
+    });
+});
+"#;
+
 /// Create an HTML document. This function generates the HTML document using
 /// the provided navigation and page body.
 ///
 /// # Arguments
 /// * `navigation` - The navigation section of the HTML document.
 /// * `page_body` - The body section of the HTML document.
+/// * `search_index` - The search index of the documentation, as a JSON array. Not embedded in the pages of a site.
 /// * `repo_name` - The name of the repository.
 /// * `repo_description` - The description of the repository.
 /// * `commit_hash` - The commit hash of the repository.
 /// * `repo_url` - The URL of the repository.
+/// * `config` - The configuration of the documentation generation.
 ///
 /// # Returns
 /// A string containing the HTML document.
@@ -19,18 +963,48 @@ use html_minifier::HTMLMinifier;
 /// If the provided HTML is invalid.
 ///
 #[must_use]
-#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 pub fn create_html_document(
     navigation: &str,
     page_body: &str,
+    search_index: &str,
     repo_name: &str,
     repo_description: &str,
     commit_hash: &str,
     repo_url: &str,
+    config: &Config,
 ) -> String {
     let mut html_minifier: HTMLMinifier = HTMLMinifier::new();
     let generation_date: String = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
+    // The mermaid renderer is only loaded when the page has diagrams to render,
+    // the pages of a site share it instead of embedding it
+    let mermaid_script: String = match &config.mermaid_script {
+        Some(_) if config.format == OutputFormat::Site && page_body.contains(MERMAID_DIAGRAM_START) => format!(
+            "<script src=\"{SITE_MERMAID_SCRIPT_PATH}\"></script><script>mermaid.initialize({{ startOnLoad: true }});</script>"
+        ),
+        Some(script) if page_body.contains(MERMAID_DIAGRAM_START) => format!(
+            "<script>{}</script><script>mermaid.initialize({{ startOnLoad: true }});</script>",
+            script.replace("</script", "<\\/script")
+        ),
+        _ => String::default(),
+    };
+
+    let commit_link: String = build_commit_link(repo_url, commit_hash, config);
+
+    // The pages of a site share their style, their script and the search index
+    let (style, script): (String, String) = if config.format == OutputFormat::Site {
+        (
+            format!("<link rel=\"stylesheet\" href=\"{SITE_STYLE_PATH}\">"),
+            format!("<script src=\"{SITE_SEARCH_INDEX_PATH}\"></script><script src=\"{SITE_SCRIPT_PATH}\"></script>"),
+        )
+    } else {
+        (
+            format!("<style>{DOCUMENT_STYLE}</style>"),
+            format!("<script type=\"application/json\" id=\"search-index\">{search_index}</script><script>{DOCUMENT_SCRIPT}</script>"),
+        )
+    };
+
     let body: String = format!(
         r#"<!DOCTYPE html>
 <html lang="en">
@@ -38,489 +1012,7 @@ pub fn create_html_document(
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>{repo_name}</title>
-    <style>
-        *,
-        *::before,
-        *::after {{
-            box-sizing: border-box;
-            margin: 0;
-            padding: 0;
-            font: inherit;
-        }}
-
-        html {{
-            font-size: 62.5%;
-        }}
-
-        :root {{
-            --bg-color: #ffffff;
-            --text-color: #333333;
-            --nav-bg-color: #f4f4f4;
-            --pre-bg-color: #f4f4f4;
-            --primary: #18181b;
-            --muted-foreground: #71717a;
-        }}
-
-        .dark-mode {{
-            --bg-color: #2d2d2d;
-            --text-color: #ffffff;
-            --nav-bg-color: #222222;
-            --pre-bg-color: #444444;
-            --primary: #fafafa;
-            --muted-foreground: #d4d4d8;
-        }}
-
-        body {{
-            line-height: 1.75;
-            color: #333;
-            height: 100%;
-            -webkit-font-smoothing: antialiased;
-            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple Color Emoji", "Segoe UI Emoji", "Segoe UI Symbol";
-            font-size: 1.6rem;
-            color: var(--text-color);
-            background-color: var(--bg-color);
-        }}
-
-        a {{
-            color: var(--muted-foreground);
-        }}
-
-        a:hover {{
-            color: var(--primary);
-        }}
-
-        .container {{
-            display: flex;
-            height: 100%;
-        }}
-
-        button {{
-            cursor: pointer;
-        }}
-
-        nav {{
-            width: 250px;
-            height: 100vh;
-            position: fixed;
-            left: 0;
-            top: 0;
-            transition: transform 0.3s ease-in-out;
-            border-right: 1px solid #ddd;
-            z-index: 1000;
-            background-color: var(--bg-color);
-            display: flex;
-            flex-direction: column;
-        }}
-
-        nav>header {{
-            border-bottom: 1px solid #ddd;
-            flex-grow: 0;
-            flex-shrink: 0;
-        }}
-
-        nav>header>.repo-name {{
-            font-family: Bahnschrift, 'DIN Alternate', 'Franklin Gothic Medium', 'Nimbus Sans Narrow', sans-serif-condensed, sans-serif;
-            font-size: 2rem;
-            padding: 10px 15px 0 15px;
-        }}
-
-        nav>header>.repo-description {{
-            padding: 0 15px 10px 15px;
-            font-size: 1.4rem;
-            border-bottom: 1px solid #ddd;
-            color: #999;
-        }}
-
-        nav>header>.commit-hash {{
-            padding: 10px 15px 0 15px;
-            font-size: 1.4rem;
-        }}
-
-        nav>header>.commit-hash>a {{
-            text-decoration: underline;
-        }}
-
-        nav>header>.generation-date {{
-            padding: 0 15px 10px 15px;
-            font-size: 1.4rem;
-        }}
-
-        nav>header>.generation-date>span:first-of-type {{
-            font-size: 1.4rem;
-            color: #999;
-        }}
-
-        nav>header>.generation-date>span:last-of-type {{
-            font-size: 1.4rem;
-            font-weight: bold;
-        }}
-
-        nav>ul {{
-            overflow-y: auto;
-            font-size: 1.4rem;
-            padding: 10px 15px 10px 20px;
-            flex-grow: 1;
-        }}
-
-        nav a {{
-            display: block;
-            text-decoration: none;
-            color: var(--text-color);
-        }}
-
-        nav a:hover {{
-            text-decoration: underline;
-        }}
-
-        nav ul {{
-            list-style-type: "→";
-            margin-bottom: 10px;
-        }}
-
-        nav ul li {{
-            padding-left: 5px;
-        }}
-
-        nav ul ul {{
-            margin-left: 10px;
-            padding: 0;
-        }}
-
-        main {{
-            flex-grow: 1;
-            margin-left: 250px;
-            overflow-y: auto;
-            position: relative;
-            overflow-x: hidden;
-            background-color: var(--bg-color);
-        }}
-
-        main>header {{
-            border-bottom: 1px solid #ddd;
-            height: 40px;
-            display: flex;
-            align-items: center;
-            justify-content: flex-end;
-            margin-bottom: 20px;
-        }}
-
-        main>footer {{
-            margin-top: 30px;
-            border-top: 1px solid #ddd;
-        }}
-
-        main>footer>p {{
-            max-width: 800px;
-            margin: 0 auto;
-            padding: 10px 15px 100px 15px;
-        }}
-
-        main>div {{
-            padding: 10px;
-            width: 100%;
-            max-width: 800px;
-            margin: 0 auto;
-        }}
-
-        main p {{
-            margin-bottom: 20px;
-        }}
-
-        .depth-2 {{
-            padding-left: 20px;
-        }}
-
-        .depth-3 {{
-            padding-left: 30px;
-        }}
-
-        .depth-4 {{
-            padding-left: 40px;
-        }}
-
-        .depth-5 {{
-            padding-left: 50px;
-        }}
-
-        .depth-6 {{
-            padding-left: 60px;
-        }}
-
-        .depth-7 {{
-            padding-left: 70px;
-        }}
-
-        .depth-8 {{
-            padding-left: 80px;
-        }}
-
-        .depth-9 {{
-            padding-left: 90px;
-        }}
-
-        .depth-10 {{
-            padding-left: 100px;
-        }}
-
-        h1,
-        h2,
-        h3,
-        h4,
-        h5,
-        h6 {{
-            padding: 10px;
-            font-family: Bahnschrift, 'DIN Alternate', 'Franklin Gothic Medium', 'Nimbus Sans Narrow', sans-serif-condensed, sans-serif;
-            width: 100%;
-            max-width: 800px;
-            margin: 0 auto;
-        }}
-
-        h1>span,
-        h2>span,
-        h3>span,
-        h4>span,
-        h5>span,
-        h6>span {{
-            display: flex;
-            align-items: center;
-            justify-content: space-between;
-        }}
-
-        h1:after,
-        h2:after,
-        h3:after,
-        h4:after,
-        h5:after,
-        h6:after {{
-            display: block;
-            width: 100%;
-            height: 1px;
-            background-color: #ddd;
-            content: '';
-        }}
-
-        h1 {{
-            font-size: 3.2rem;
-        }}
-
-        h2 {{
-            font-size: 2.8rem;
-            padding-left: 20px;
-        }}
-
-        h3 {{
-            font-size: 2.4rem;
-            padding-left: 30px;
-        }}
-
-        h4 {{
-            font-size: 2.2rem;
-            padding-left: 40px;
-        }}
-
-        h5 {{
-            font-size: 2rem;
-            padding-left: 50px;
-        }}
-
-        h6 {{
-            font-size: 1.8rem;
-            padding-left: 60px;
-        }}
-
-        pre {{
-            background-color: #f4f4f4;
-            padding: 10px;
-            overflow-x: auto;
-        }}
-
-        #menu-toggle {{
-            position: absolute;
-            top: 0;
-            right: -51px;
-            z-index: 1000;
-            color: #333;
-            border: none;
-            cursor: pointer;
-            border-bottom-right-radius: 5px;
-            border-right: 1px solid #ddd;
-            border-bottom: 1px solid #ddd;
-            background-color: #fff;
-            width: 50px;
-            text-align: center;
-            font-size: 24px;
-            height: 50px;
-            display: none;
-        }}
-
-        #print-btn>svg,
-        #mode-toggle>svg {{
-            display: block;
-            width: 18px;
-            fill: var(--text-color);
-        }}
-
-        #decrease-font,
-        #increase-font,
-        #top-repo-link,
-        #print-btn,
-        #mode-toggle {{
-            color: var(--text-color);
-            cursor: pointer;
-            background: none;
-            border: none;
-            font-weight: bold;
-            height: 100%;
-            border-left: 1px solid #ddd;
-            padding: 0 8px;
-            font-size: 16px;
-            display: flex;
-            align-items: center;
-        }}
-
-        @media (max-width:768px) {{
-            nav {{
-                transform: translateX(-100%);
-            }}
-
-            nav.active {{
-                transform: translateX(0);
-            }}
-
-            main {{
-                margin-left: 0;
-            }}
-
-            #menu-toggle {{
-                display: block;
-            }}
-        }}
-
-        .gh-icon {{
-            mask-image: url("data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 24 24'%3E%3Cpath d='M12,2A10,10 0 0,0 2,12C2,16.42 4.87,20.17 8.84,21.5C9.34,21.58 9.5,21.27 9.5,21C9.5,20.77 9.5,20.14 9.5,19.31C6.73,19.91 6.14,17.97 6.14,17.97C5.68,16.81 5.03,16.5 5.03,16.5C4.12,15.88 5.1,15.9 5.1,15.9C6.1,15.97 6.63,16.93 6.63,16.93C7.5,18.45 8.97,18 9.54,17.76C9.63,17.11 9.89,16.67 10.17,16.42C7.95,16.17 5.62,15.31 5.62,11.5C5.62,10.39 6,9.5 6.65,8.79C6.55,8.54 6.2,7.5 6.75,6.15C6.75,6.15 7.59,5.88 9.5,7.17C10.29,6.95 11.15,6.84 12,6.84C12.85,6.84 13.71,6.95 14.5,7.17C16.41,5.88 17.25,6.15 17.25,6.15C17.8,7.5 17.45,8.54 17.35,8.79C18,9.5 18.38,10.39 18.38,11.5C18.38,15.32 16.04,16.16 13.81,16.41C14.17,16.72 14.5,17.33 14.5,18.26C14.5,19.6 14.5,20.68 14.5,21C14.5,21.27 14.66,21.59 15.17,21.5C19.14,20.16 22,16.42 22,12A10,10 0 0,0 12,2Z'/%3E%3C/svg%3E");
-            background-color: var(--text-color);
-            background-size: contain;
-            width: 20px;
-            height: 20px;
-            display: block;
-            flex-shrink: 0;
-            background-repeat: no-repeat;
-            background-position: center;
-        }}
-
-        code,
-        pre {{
-            font-family: ui-monospace, 'Cascadia Code', 'Source Code Pro', Menlo, Consolas, 'DejaVu Sans Mono', monospace;
-            background-color: #f4f4f4;
-            border-radius: 4px;
-            color: #333;
-            border: 1px solid #ddd;
-        }}
-
-        pre code {{
-            background: none;
-            border: none;
-        }}
-
-        code {{
-            padding: 2px 4px;
-            font-size: 90%;
-            text-wrap: wrap;
-        }}
-
-        pre {{
-            padding: 10px;
-            overflow-x: auto;
-        }}
-
-        pre code {{
-            background-color: transparent;
-            padding: 0;
-        }}
-
-        blockquote {{
-            border-left: 3px solid #999;
-            padding: 5px 5px 5px 15px;
-            background: #66666611;
-            margin-bottom: 20px;
-        }}
-
-        table {{
-            border-collapse: collapse;
-            width: 100%;
-            margin-bottom: 10px;
-        }}
-
-        th,
-        td {{
-            border: 1px solid #ddd;
-            padding: 8px;
-            text-align: left;
-        }}
-
-        th {{
-            background-color: #00000022;
-            font-weight: bold;
-        }}
-
-        tr:nth-child(even) {{
-            background-color: #00000011;
-        }}
-
-        ul,
-        ol {{
-            padding-left: 20px;
-            margin-bottom: 20px;
-        }}
-
-        ul {{
-            list-style-type: disc;
-        }}
-
-        ol {{
-            list-style-type: decimal;
-        }}
-
-        li {{
-            margin-bottom: 5px;
-        }}
-
-        hr {{
-            border: none;
-            border-top: 1px solid #ddd;
-            margin: 20px 0;
-        }}
-
-        strong {{
-            font-weight: 700;
-        }}
-
-        em {{
-            font-style: italic;
-        }}
-
-        @media print {{
-            main>header {{
-                display: none;
-            }}
-
-            body {{
-                font-size: 12pt;
-            }}
-
-            #menu-toggle{{
-               display: none;
-            }}
-
-            nav {{
-                width: 100%;
-                height: auto;
-                position: relative;
-                transform: translateX(0);
-                border-right: none;
-                display: block;
-            }}
-    </style>
+    {style}
 </head>
 <body>
     <nav>
@@ -529,7 +1021,7 @@ pub fn create_html_document(
             <p class="repo-name">{repo_name}</p>
             <p class="repo-description">{repo_description}</p>
             <p class="commit-hash">
-                <a href="{repo_url}/commit/{commit_hash}" target="_blank">commit {commit_hash}</a>
+                <a href="{commit_link}" target="_blank">commit {commit_hash}</a>
             </p>
             <p class="generation-date">
                 <span>generated at : </span>
@@ -540,6 +1032,10 @@ pub fn create_html_document(
     </nav>
     <main>
         <header>
+            <div id="search">
+                <input id="search-input" type="search" placeholder="Search" aria-label="Search the documentation" autocomplete="off">
+                <ul id="search-results"></ul>
+            </div>
             <button id="print-btn" type="button" title="Print documentation">
             	<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
                   <path d="M18 3H6v4h12m1 5a1 1 0 0 1-1-1 1 1 0 0 1 1-1 1 1 0 0 1 1 1 1 1 0 0 1-1 1m-3 7H8v-5h8m3-6H5a3 3 0 0 0-3 3v6h4v4h12v-4h4v-6a3 3 0 0 0-3-3Z"/>
@@ -561,49 +1057,8 @@ pub fn create_html_document(
         <footer>
         </footer>
     </main>
-    <script>
-        document.addEventListener('DOMContentLoaded', () => {{
-
-            const html = document.documentElement;
-
-            const getCurrentFontSize = () => parseFloat(window.getComputedStyle(html).fontSize);
-            const setFontSize = (newSize) => html.style.fontSize = newSize + 'px';
-            document.getElementById('increase-font').addEventListener('click', () => {{
-                const currentSize = getCurrentFontSize();
-                setFontSize(Math.min(currentSize + 2, 24));
-            }});
-            document.getElementById('decrease-font').addEventListener('click', () => {{
-                const currentSize = getCurrentFontSize();
-                setFontSize(Math.max(currentSize - 2, 8));
-            }});
-
-            document.getElementById('menu-toggle').addEventListener('click', () => {{
-                document.querySelector('nav').classList.toggle('active');
-            }});
-            window.addEventListener('resize', () => {{
-                document.querySelector('nav').classList.remove('active');
-            }});
-
-            const printBtn = document.getElementById('print-btn');
-            printBtn.addEventListener('click', () => {{
-                window.print();
-            }});
-
-            const setTheme = (isDark) => {{
-                document.body.classList.toggle('dark-mode', isDark);
-                localStorage.setItem('storedTheme', isDark ? 'dark' : 'light')
-            }};
-            document.getElementById('mode-toggle').addEventListener('click', () => {{
-                setTheme(!document.body.classList.contains('dark-mode'))
-            }});
-            const storedTheme = localStorage.getItem('storedTheme');
-            if (storedTheme) {{
-                setTheme(storedTheme === 'dark');
-            }} else {{
-                setTheme(window.matchMedia('(prefers-color-scheme: dark)').matches);
-            }}
-        }});
-    </script>
+    {mermaid_script}
+    {script}
 </body>
 </html>"#
     );
@@ -611,3 +1066,23 @@ pub fn create_html_document(
     html_minifier.digest(body).unwrap();
     String::from_utf8_lossy(html_minifier.get_html()).into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_html_document_mermaid_script() {
+        let config: Config = Config {
+            mermaid_script: Some("window.mermaid = {};".to_string()),
+            ..Config::default()
+        };
+
+        let without_diagram: String = create_html_document("", "<p>Text</p>", "[]", "Repo", "", "ebb34e7", "", &config);
+        assert!(!without_diagram.contains("window.mermaid"));
+
+        let with_diagram: String = create_html_document("", "<pre class=\"mermaid\">graph TD;</pre>", "[]", "Repo", "", "ebb34e7", "", &config);
+        // The scripts are minified with the rest of the document
+        assert!(with_diagram.contains("<script>window.mermaid={};</script><script>mermaid.initialize({startOnLoad:true});</script>"));
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// A warning raised while generating the documentation.
+/// - `file_path`: The path of the file the warning is about, if any.
+/// - `message`: The description of the warning.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file_path: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Create a new `Diagnostic`.
+    /// - `file_path`: The path of the file the warning is about, if any.
+    /// - `message`: The description of the warning.
+    #[must_use]
+    pub fn new(file_path: Option<&str>, message: &str) -> Self {
+        Diagnostic {
+            file_path: file_path.map(ToString::to_string),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file_path {
+            Some(file_path) => write!(f, "{}: {}", file_path, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
@@ -1,12 +1,20 @@
 use std::collections::HashMap;
 
+use crate::core::repository::Repository;
+
 /// A node in the documentation tree.
 /// - `children`: The children of the node.
 /// - `content`: The content of the node.
 /// - `depth`: The depth of the node in the tree.
+/// - `description`: The one-line summary of the node, from its Front Matter.
+/// - `end_line`: The last line of the content in the file, numbered from 1.
 /// - `file_path`: The path of the file containing the node.
+/// - `front_matter`: The YAML source of the Front Matter of the file containing the node.
 /// - `path`: The path of the node.
 /// - `position`: The position of the node in the documentation structure.
+/// - `repository`: The repository the node and its children come from, when it is not the one of the documentation.
+/// - `start_line`: The first line of the content in the file, numbered from 1.
+/// - `tags`: The tags of the node, from its Front Matter.
 /// - `title`: The title of the node.
 /// 
 #[derive(Debug, Clone)]
@@ -14,9 +22,15 @@ pub struct DocNode {
     pub children: HashMap<String, DocNode>,
     pub content: Option<String>,
     pub depth: usize,
+    pub description: Option<String>,
+    pub end_line: Option<usize>,
     pub file_path: Option<String>,
+    pub front_matter: Option<String>,
     pub path: String,
     pub position: Option<usize>,
+    pub repository: Option<Repository>,
+    pub start_line: Option<usize>,
+    pub tags: Vec<String>,
     pub title: String,
 }
 
@@ -30,10 +44,36 @@ impl DocNode {
             children: HashMap::new(),
             content: None,
             depth: 0,
+            description: None,
+            end_line: None,
             file_path: None,
+            front_matter: None,
             path: path.to_string(),
             position: None,
+            repository: None,
+            start_line: None,
+            tags: Vec::new(),
             title: title.to_string(),
         }
     }
+
+    /// The anchor of the node in the HTML document.
+    #[must_use]
+    pub fn id(&self) -> String {
+        self.path.replace(' ', "-").to_lowercase()
+    }
+
+    /// The children of the node in display order: first the ones with a
+    /// position, by position, then the others by title.
+    #[must_use]
+    pub fn sorted_children(&self) -> Vec<&DocNode> {
+        let mut children: Vec<&DocNode> = self.children.values().collect();
+        children.sort_by(|a, b| match (a.position, b.position) {
+            (Some(pos_a), Some(pos_b)) => pos_a.cmp(&pos_b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.title.cmp(&b.title),
+        });
+        children
+    }
 }
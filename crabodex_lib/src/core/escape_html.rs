@@ -0,0 +1,41 @@
+/// Escapes a text so it can be safely inserted in an HTML document,
+/// including in attribute values.
+///
+/// # Arguments
+/// * `text` - The text to escape.
+///
+/// # Returns
+/// The escaped text.
+///
+#[must_use]
+pub fn escape_html(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverts the escaping of `escape_html`.
+///
+/// # Arguments
+/// * `text` - The escaped text.
+///
+/// # Returns
+/// The text as it was before being escaped.
+///
+#[must_use]
+pub fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
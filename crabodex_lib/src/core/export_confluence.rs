@@ -0,0 +1,454 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use pulldown_cmark::html::push_html;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Parser, Tag, TagEnd};
+use serde::Serialize;
+
+use crate::core::collect_headings::{collect_headings, slugify};
+use crate::core::constants::CONFLUENCE_MANIFEST_PATH;
+use crate::core::escape_html::escape_html;
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_admonitions::{expand_admonition_containers, replace_admonitions};
+use crate::core::repository::Repository;
+use crate::core::resolve_includes::{follow_transclusion_marker, paired_transclusion_markers, transclusion_marker};
+use crate::core::resolve_relative_path::resolve_relative_path;
+use crate::DocNode;
+
+/// The Confluence macros of the admonitions, by kind. The other kinds are rendered as panels.
+const ADMONITION_MACROS: &[(&str, &str)] = &[
+    ("note", "info"),
+    ("tip", "tip"),
+    ("important", "note"),
+    ("warning", "warning"),
+    ("caution", "warning"),
+    ("deprecated", "note"),
+];
+
+/// The body of the pages without content: the list of their children.
+const CHILDREN_MACRO: &str = "<ac:structured-macro ac:name=\"children\"/>";
+
+/// The manifest of the pages exported for Confluence.
+/// - `title`: The name of the repository.
+/// - `description`: The description of the repository.
+/// - `commit_hash`: The commit the documentation was generated from.
+/// - `pages`: The top-level pages, in display order.
+///
+#[derive(Debug, Serialize)]
+struct ConfluenceManifest {
+    title: String,
+    description: String,
+    commit_hash: String,
+    pages: Vec<ConfluencePage>,
+}
+
+/// A page exported for Confluence.
+/// - `title`: The title of the page.
+/// - `file`: The storage-format XHTML of the page, relative to the output directory.
+/// - `source_file`: The markdown file of the page, relative to the root directory.
+/// - `attachments`: The local images of the page, to attach to it.
+/// - `children`: The child pages, in display order.
+///
+#[derive(Debug, Serialize)]
+struct ConfluencePage {
+    title: String,
+    file: String,
+    source_file: Option<String>,
+    attachments: Vec<ConfluenceAttachment>,
+    children: Vec<ConfluencePage>,
+}
+
+/// A local image attached to a page.
+/// - `file_name`: The name of the attachment, used by the page.
+/// - `path`: The path of the image, relative to the root directory.
+///
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct ConfluenceAttachment {
+    file_name: String,
+    path: String,
+}
+
+/// Exports the documentation as Confluence storage-format pages.
+///
+/// Every section is a page in the `pages` directory, and the manifest lists them in a hierarchy
+/// mirroring the documentation tree, with the local images to attach to them. The code blocks are
+/// code macros with their language, the admonitions are panel macros, and the links to the other
+/// sections point at their pages. The raw HTML is displayed as text, as the storage format only
+/// accepts well-formed XHTML.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `repository` - The repository the documentation comes from.
+///
+/// # Returns
+/// The pages and the manifest relative to the output directory, with their content.
+///
+/// # Panics
+/// If the manifest cannot be serialized.
+///
+#[must_use]
+pub fn export_confluence(root: &DocNode, repository: &Repository) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut unique_titles: HashMap<String, String> = HashMap::new();
+    collect_unique_titles(root, &title_counts(root), &mut HashSet::new(), &mut unique_titles);
+    let mut page_titles: HashMap<String, String> = HashMap::new();
+    collect_page_titles(root, &unique_titles, &mut page_titles);
+
+    let mut files: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let pages: Vec<ConfluencePage> = confluence_pages(root, &unique_titles, &page_titles, &mut HashSet::new(), &mut files);
+    let manifest: ConfluenceManifest = ConfluenceManifest {
+        title: repository.name.clone(),
+        description: repository.description.clone(),
+        commit_hash: repository.commit_hash.clone(),
+        pages,
+    };
+    files.push((PathBuf::from(CONFLUENCE_MANIFEST_PATH), serde_json::to_vec_pretty(&manifest).unwrap()));
+
+    files
+}
+
+/// Counts the sections of the documentation by title.
+fn title_counts(root: &DocNode) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut nodes: Vec<&DocNode> = root.children.values().collect();
+    while let Some(node) = nodes.pop() {
+        *counts.entry(node.title.clone()).or_default() += 1;
+        nodes.extend(node.children.values());
+    }
+    counts
+}
+
+/// Maps the anchors of the sections to the titles of their pages, unique in the Confluence space.
+///
+/// The titles shared by several sections are qualified with the titles of their parents,
+/// and get a numbered suffix if they are still not unique.
+fn collect_unique_titles(
+    node: &DocNode,
+    title_counts: &HashMap<String, usize>,
+    used_titles: &mut HashSet<String>,
+    unique_titles: &mut HashMap<String, String>,
+) {
+    for child in node.sorted_children() {
+        let title: String = if title_counts.get(&child.title).copied().unwrap_or_default() > 1 {
+            child.path.clone()
+        } else {
+            child.title.clone()
+        };
+        let mut unique_title: String = title.clone();
+        let mut count: usize = 1;
+        while !used_titles.insert(unique_title.clone()) {
+            count += 1;
+            unique_title = format!("{title} ({count})");
+        }
+        unique_titles.insert(child.id(), unique_title);
+        collect_unique_titles(child, title_counts, used_titles, unique_titles);
+    }
+}
+
+/// Maps the anchors of the sections and of their headings to the title of the page they are on.
+fn collect_page_titles(node: &DocNode, unique_titles: &HashMap<String, String>, page_titles: &mut HashMap<String, String>) {
+    for child in node.children.values() {
+        let title: String = unique_titles.get(&child.id()).cloned().unwrap_or_else(|| child.title.clone());
+        for heading in collect_headings(child) {
+            page_titles.insert(heading.id, title.clone());
+        }
+        page_titles.insert(child.id(), title);
+        collect_page_titles(child, unique_titles, page_titles);
+    }
+}
+
+/// Builds the pages of the children of a node, with their own children, recursively.
+fn confluence_pages(
+    node: &DocNode,
+    unique_titles: &HashMap<String, String>,
+    page_titles: &HashMap<String, String>,
+    file_names: &mut HashSet<String>,
+    files: &mut Vec<(PathBuf, Vec<u8>)>,
+) -> Vec<ConfluencePage> {
+    let mut pages: Vec<ConfluencePage> = Vec::new();
+
+    for child in node.sorted_children() {
+        let mut attachments: Vec<ConfluenceAttachment> = Vec::new();
+        let body: String = match &child.content {
+            Some(content) if !content.trim().is_empty() => storage_format(child, content, page_titles, &mut attachments),
+            _ => CHILDREN_MACRO.to_string(),
+        };
+
+        // The file names are unique, the sections with the same slug get a numbered suffix
+        let slug: String = slugify(&child.id());
+        let mut file: String = format!("pages/{slug}.xhtml");
+        let mut count: usize = 1;
+        while !file_names.insert(file.clone()) {
+            count += 1;
+            file = format!("pages/{slug}-{count}.xhtml");
+        }
+        files.push((PathBuf::from(&file), body.into_bytes()));
+
+        pages.push(ConfluencePage {
+            title: unique_titles.get(&child.id()).cloned().unwrap_or_else(|| child.title.clone()),
+            file,
+            source_file: child.file_path.clone(),
+            attachments,
+            children: confluence_pages(child, unique_titles, page_titles, file_names, files),
+        });
+    }
+
+    pages
+}
+
+/// Renders the markdown content of a node in the Confluence storage format.
+fn storage_format(
+    node: &DocNode,
+    content: &str,
+    page_titles: &HashMap<String, String>,
+    attachments: &mut Vec<ConfluenceAttachment>,
+) -> String {
+    let content: String = expand_admonition_containers(content);
+    let mut events: Vec<Event> = Vec::new();
+    // The language and the text of the current code block
+    let mut code_block: Option<(String, String)> = None;
+    // The resource and the alternative text of the current image
+    let mut image: Option<(String, String)> = None;
+    // Whether each of the open links points at a page
+    let mut links: Vec<bool> = Vec::new();
+    let markdown_events: Vec<Event> = Parser::new_ext(&content, markdown_options()).collect();
+    let transclusion_markers: HashSet<usize> = paired_transclusion_markers(&markdown_events);
+    // The file the current content comes from, the last included one or the file of the node
+    let mut file_paths: Vec<String> = node.file_path.iter().cloned().collect();
+
+    for (index, event) in markdown_events.into_iter().enumerate() {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language: String = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_lowercase(),
+                    CodeBlockKind::Indented => String::default(),
+                };
+                code_block = Some((language, String::default()));
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = &mut code_block {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = code_block.take() {
+                    events.push(Event::Html(CowStr::Boxed(code_macro(&language, &code).into())));
+                }
+            }
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                let file_path: Option<&str> = file_paths.last().map(String::as_str);
+                image = Some((image_resource(file_path, &dest_url, attachments), String::default()));
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((resource, alt)) = image.take() {
+                    events.push(Event::Html(CowStr::Boxed(
+                        format!("<ac:image ac:alt=\"{}\">{resource}</ac:image>", escape_html(&alt)).into(),
+                    )));
+                }
+            }
+            Event::Text(text) | Event::Code(text) if image.is_some() => {
+                if let Some((_, alt)) = &mut image {
+                    alt.push_str(&text);
+                }
+            }
+            _ if image.is_some() => {}
+            Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+                let page_title: Option<&String> = dest_url.strip_prefix('#').and_then(|anchor| page_titles.get(anchor));
+                match page_title {
+                    Some(page_title) => {
+                        links.push(true);
+                        events.push(Event::Html(CowStr::Boxed(
+                            format!("<ac:link><ri:page ri:content-title=\"{}\"/><ac:link-body>", escape_html(page_title)).into(),
+                        )));
+                    }
+                    None => {
+                        links.push(false);
+                        events.push(Event::Start(Tag::Link { link_type, dest_url, title, id }));
+                    }
+                }
+            }
+            event @ Event::End(TagEnd::Link) => {
+                events.push(if links.pop() == Some(true) {
+                    Event::Html(CowStr::Borrowed("</ac:link-body></ac:link>"))
+                } else {
+                    event
+                });
+            }
+            // The markers of the included files are dropped, the images are resolved against these files
+            Event::Html(raw) if transclusion_marker(&raw).is_some() => {
+                if let Some(marker) = transclusion_marker(&raw).filter(|_| transclusion_markers.contains(&index)) {
+                    follow_transclusion_marker(&mut file_paths, &marker);
+                }
+            }
+            // The storage format only accepts well-formed XHTML, the raw HTML is displayed as text
+            Event::Html(raw) | Event::InlineHtml(raw) => events.push(Event::Text(raw)),
+            event => events.push(event),
+        }
+    }
+
+    let events: Vec<Event> = replace_admonitions(events, panel_macro_start, "</ac:rich-text-body></ac:structured-macro>");
+    let mut html: String = String::with_capacity(content.len() * 3 / 2);
+    push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Builds the code macro of a code block, with its language when it has one.
+fn code_macro(language: &str, code: &str) -> String {
+    let language: String = if language.is_empty() {
+        String::default()
+    } else {
+        format!("<ac:parameter ac:name=\"language\">{}</ac:parameter>", escape_html(language))
+    };
+    // A `]]>` in the code would close the CDATA section, it is split in two sections
+    format!(
+        "<ac:structured-macro ac:name=\"code\">{language}<ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body></ac:structured-macro>",
+        code.replace("]]>", "]]]]><![CDATA[>")
+    )
+}
+
+/// Builds the markup opening the panel macro of an admonition.
+fn panel_macro_start(kind: &str, title: &str) -> String {
+    let name: &str = ADMONITION_MACROS
+        .iter()
+        .find(|(admonition_kind, _)| *admonition_kind == kind)
+        .map_or("panel", |(_, name)| name);
+    format!(
+        "<ac:structured-macro ac:name=\"{name}\"><ac:parameter ac:name=\"title\">{}</ac:parameter><ac:rich-text-body>",
+        escape_html(title)
+    )
+}
+
+/// Builds the resource of an image: an attachment of the page for the local images, a URL for the others.
+///
+/// The local images are resolved against the file they are written in, the node's or an included one.
+fn image_resource(file_path: Option<&str>, url: &str, attachments: &mut Vec<ConfluenceAttachment>) -> String {
+    let has_scheme: bool = url
+        .find(':')
+        .is_some_and(|index| !url[..index].contains(['/', '?', '#']));
+    let path: Option<PathBuf> = file_path
+        .filter(|_| !has_scheme && !url.starts_with('/'))
+        .and_then(|file_path| resolve_relative_path(file_path, url.split(['?', '#']).next().unwrap_or_default()));
+    let file_name: Option<String> = path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|file_name| file_name.to_string_lossy().to_string());
+
+    let (Some(path), Some(file_name)) = (path, file_name) else {
+        return format!("<ri:url ri:value=\"{}\"/>", escape_html(url));
+    };
+    let path: String = path.to_string_lossy().replace('\\', "/");
+    let file_name: String = match attachments.iter().find(|attachment| attachment.path == path) {
+        Some(attachment) => attachment.file_name.clone(),
+        None => {
+            let file_name: String = unique_file_name(&file_name, attachments);
+            attachments.push(ConfluenceAttachment { file_name: file_name.clone(), path });
+            file_name
+        }
+    };
+    format!("<ri:attachment ri:filename=\"{}\"/>", escape_html(&file_name))
+}
+
+/// Makes the name of an attachment unique on its page, the images with the same name get a numbered suffix.
+fn unique_file_name(file_name: &str, attachments: &[ConfluenceAttachment]) -> String {
+    let (stem, extension): (&str, &str) = match file_name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => (stem, &file_name[stem.len()..]),
+        _ => (file_name, ""),
+    };
+    let mut unique_file_name: String = file_name.to_string();
+    let mut count: usize = 1;
+    while attachments.iter().any(|attachment| attachment.file_name == unique_file_name) {
+        count += 1;
+        unique_file_name = format!("{stem}-{count}{extension}");
+    }
+    unique_file_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_confluence() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        let mut discounts: DocNode = DocNode::new("Discounts", "Pricing > Discounts");
+        discounts.file_path = Some("pricing/discounts.md".to_string());
+        discounts.content = Some(
+            "See the [refunds](#refunds).\n\n```rs\nlet a = [[1]]>0;\n```\n\n:::rule\nNever stack.\n:::\n\n![Chart](images/chart.png)\n\n<b>raw</b>"
+                .to_string(),
+        );
+        pricing.children.insert("Discounts".to_string(), discounts);
+        root.children.insert("Pricing".to_string(), pricing);
+        root.children.insert("Refunds".to_string(), DocNode::new("Refunds", "Refunds"));
+
+        let files: Vec<(PathBuf, Vec<u8>)> = export_confluence(&root, &Repository::default());
+        let file_names: Vec<String> = files.iter().map(|(path, _)| path.to_string_lossy().to_string()).collect();
+        assert_eq!(
+            file_names,
+            vec!["pages/pricing.xhtml", "pages/pricing-discounts.xhtml", "pages/refunds.xhtml", CONFLUENCE_MANIFEST_PATH]
+        );
+
+        assert_eq!(String::from_utf8(files[0].1.clone()).unwrap(), CHILDREN_MACRO);
+        let discounts_page: String = String::from_utf8(files[1].1.clone()).unwrap();
+        assert!(discounts_page.contains("<ac:link><ri:page ri:content-title=\"Refunds\"/><ac:link-body>refunds</ac:link-body></ac:link>"));
+        assert!(discounts_page.contains("<ac:structured-macro ac:name=\"code\"><ac:parameter ac:name=\"language\">rs</ac:parameter><ac:plain-text-body><![CDATA[let a = [[1]]]]><![CDATA[>0;\n]]></ac:plain-text-body></ac:structured-macro>"));
+        assert!(discounts_page.contains("<ac:structured-macro ac:name=\"panel\"><ac:parameter ac:name=\"title\">Rule</ac:parameter><ac:rich-text-body>"));
+        assert!(discounts_page.contains("<ac:image ac:alt=\"Chart\"><ri:attachment ri:filename=\"chart.png\"/></ac:image>"));
+        assert!(discounts_page.contains("&lt;b&gt;raw&lt;/b&gt;"));
+
+        let manifest: serde_json::Value = serde_json::from_slice(&files[3].1).unwrap();
+        let discounts_entry: &serde_json::Value = &manifest["pages"][0]["children"][0];
+        assert_eq!(discounts_entry["file"], "pages/pricing-discounts.xhtml");
+        assert_eq!(discounts_entry["attachments"][0]["path"], "pricing/images/chart.png");
+    }
+
+    #[test]
+    fn test_export_confluence_transclusion_images() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut discounts: DocNode = DocNode::new("Discounts", "Discounts");
+        discounts.file_path = Some("pricing/discounts.md".to_string());
+        discounts.content = Some(
+            "<!-- crabodex:transclusion-start shared/legal.md -->\n\n![Seal](images/seal.png)\n\n<!-- crabodex:transclusion-end -->\n\n![Chart](images/chart.png)"
+                .to_string(),
+        );
+        root.children.insert("Discounts".to_string(), discounts);
+
+        let files: Vec<(PathBuf, Vec<u8>)> = export_confluence(&root, &Repository::default());
+
+        let manifest: serde_json::Value = serde_json::from_slice(&files[1].1).unwrap();
+        let attachments: &serde_json::Value = &manifest["pages"][0]["attachments"];
+        assert_eq!(attachments[0]["path"], "shared/images/seal.png");
+        assert_eq!(attachments[1]["path"], "pricing/images/chart.png");
+    }
+
+    #[test]
+    fn test_export_confluence_unique_names() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        let mut pricing_overview: DocNode = DocNode::new("Overview", "Pricing > Overview");
+        pricing_overview.file_path = Some("pricing/overview.md".to_string());
+        pricing_overview.content = Some("![A](a/chart.png) ![B](b/chart.png) ![A again](a/chart.png)".to_string());
+        pricing.children.insert("Overview".to_string(), pricing_overview);
+        let mut refunds: DocNode = DocNode::new("Refunds", "Refunds");
+        let mut refunds_overview: DocNode = DocNode::new("Overview", "Refunds > Overview");
+        refunds_overview.content = Some("See the [pricing](#pricing->-overview).".to_string());
+        refunds.children.insert("Overview".to_string(), refunds_overview);
+        root.children.insert("Pricing".to_string(), pricing);
+        root.children.insert("Refunds".to_string(), refunds);
+
+        let files: Vec<(PathBuf, Vec<u8>)> = export_confluence(&root, &Repository::default());
+
+        let manifest: serde_json::Value = serde_json::from_slice(&files.last().unwrap().1).unwrap();
+        let pricing_overview_entry: &serde_json::Value = &manifest["pages"][0]["children"][0];
+        assert_eq!(pricing_overview_entry["title"], "Pricing > Overview");
+        assert_eq!(manifest["pages"][1]["children"][0]["title"], "Refunds > Overview");
+        assert_eq!(pricing_overview_entry["attachments"][0]["file_name"], "chart.png");
+        assert_eq!(pricing_overview_entry["attachments"][1]["file_name"], "chart-2.png");
+        assert_eq!(pricing_overview_entry["attachments"][1]["path"], "pricing/b/chart.png");
+        assert_eq!(pricing_overview_entry["attachments"].as_array().unwrap().len(), 2);
+
+        let pricing_overview_page: String = String::from_utf8(files[1].1.clone()).unwrap();
+        assert!(pricing_overview_page.contains("<ri:attachment ri:filename=\"chart-2.png\"/>"));
+        let refunds_overview_page: String = String::from_utf8(files[3].1.clone()).unwrap();
+        assert!(refunds_overview_page.contains("<ri:page ri:content-title=\"Pricing &gt; Overview\"/>"));
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+
+use crate::core::build_page_body::build_section;
+use crate::core::build_site::rewrite_anchor_links;
+use crate::core::build_zip_archive::build_zip_archive;
+use crate::core::collect_headings::{collect_headings, slugify};
+use crate::core::config::{Config, HtmlPolicy, ImageMode};
+use crate::core::constants::EPUB_LANGUAGE;
+use crate::core::create_html_document::DOCUMENT_STYLE;
+use crate::core::diagnostic::Diagnostic;
+use crate::core::escape_html::escape_html;
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// An image of the book, extracted from the chapters.
+/// - `path`: The path of the image in the book, relative to the package document.
+/// - `media_type`: The MIME type of the image.
+/// - `content`: The content of the image.
+///
+struct EpubImage {
+    path: String,
+    media_type: String,
+    content: Vec<u8>,
+}
+
+/// Exports the documentation as an EPUB 3 book.
+///
+/// Every top-level section is a chapter, with its subsections. The table of contents lists the
+/// sections in the order of the navigation, and the links to the anchors of the other chapters
+/// point at them. The local images and the stylesheet of the HTML document are in the book,
+/// so it can be read offline. The metadata come from the repository and its commit. The raw HTML
+/// of the markdown content is displayed as text, whatever the HTML policy.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `repository` - The repository the documentation comes from.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while rendering the chapters are added to.
+///
+/// # Returns
+/// The book relative to the output directory, with its content.
+///
+#[must_use]
+pub fn export_epub(
+    root: &DocNode,
+    root_directory: &Path,
+    repository: &Repository,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<(PathBuf, Vec<u8>)> {
+    // The images are embedded while rendering, then extracted to files of the book. The raw HTML
+    // is escaped, the chapters must be well-formed XML and it rarely is
+    let config: Config = Config { image_mode: ImageMode::Embed, html_policy: HtmlPolicy::Escape, ..config.clone() };
+    let title: &str = if repository.name.is_empty() { "Documentation" } else { &repository.name };
+    let repository_link: (&str, &str) = (&repository.url, &repository.source_ref);
+
+    let sections: Vec<&DocNode> = root.sorted_children();
+    let mut anchors: HashMap<String, String> = HashMap::new();
+    for (index, section) in sections.iter().enumerate() {
+        chapter_anchors(section, &format!("chapter-{}.xhtml", index + 1), &mut anchors);
+    }
+
+    let mut chapters: Vec<(String, String)> = Vec::new();
+    let mut images: Vec<EpubImage> = Vec::new();
+    for (index, section) in sections.iter().enumerate() {
+        let file_name: String = format!("chapter-{}.xhtml", index + 1);
+        let siblings: (Option<&DocNode>, Option<&DocNode>) =
+            (index.checked_sub(1).map(|previous| sections[previous]), sections.get(index + 1).copied());
+        let body: String = build_section(section, root_directory, 1, siblings, repository_link, &config, diagnostics);
+        let body: String = extract_images(&rewrite_anchor_links(&body, &file_name, &anchors), &mut images);
+        chapters.push((file_name, xhtml_document(&section.title, &body)));
+    }
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        // The media type comes first, uncompressed, so the book is recognized as such
+        ("mimetype".to_string(), b"application/epub+zip".to_vec()),
+        ("META-INF/container.xml".to_string(), CONTAINER_XML.as_bytes().to_vec()),
+        ("OEBPS/content.opf".to_string(), package_document(title, repository, &chapters, &images).into_bytes()),
+        ("OEBPS/nav.xhtml".to_string(), xhtml_document(title, &navigation_body(root, &anchors)).into_bytes()),
+        ("OEBPS/title.xhtml".to_string(), xhtml_document(title, &title_body(title, repository)).into_bytes()),
+        ("OEBPS/style.css".to_string(), DOCUMENT_STYLE.as_bytes().to_vec()),
+    ];
+    for (file_name, chapter) in chapters {
+        files.push((format!("OEBPS/{file_name}"), chapter.into_bytes()));
+    }
+    for image in images {
+        files.push((format!("OEBPS/{}", image.path), image.content));
+    }
+
+    let file_name: String = format!("{}.epub", slugify(title));
+    vec![(PathBuf::from(file_name), build_zip_archive(&files))]
+}
+
+/// The container document, pointing at the package document.
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// Maps the anchors of a section, of its subsections and of their headings to the chapter they are in.
+fn chapter_anchors(node: &DocNode, file_name: &str, anchors: &mut HashMap<String, String>) {
+    anchors.insert(node.id(), file_name.to_string());
+    for heading in collect_headings(node) {
+        anchors.insert(heading.id, file_name.to_string());
+    }
+    for child in node.children.values() {
+        chapter_anchors(child, file_name, anchors);
+    }
+}
+
+/// Moves the images embedded in the HTML of a chapter to files of the book.
+///
+/// The same image embedded several times is only stored once.
+fn extract_images(html: &str, images: &mut Vec<EpubImage>) -> String {
+    let mut extracted: String = String::with_capacity(html.len());
+    let mut rest: &str = html;
+
+    while let Some(index) = rest.find("src=\"data:") {
+        let (before, after) = rest.split_at(index + "src=\"".len());
+        extracted.push_str(before);
+
+        let data_uri_end: usize = after.find('"').unwrap_or(after.len());
+        let data_uri: &str = &after[..data_uri_end];
+        let image: Option<(&str, Vec<u8>)> = data_uri
+            .strip_prefix("data:")
+            .and_then(|data_uri| data_uri.split_once(";base64,"))
+            .and_then(|(media_type, data)| Some((media_type, STANDARD.decode(data).ok()?)));
+
+        match image {
+            Some((media_type, content)) => {
+                let path: String = match images.iter().find(|image| image.content == content) {
+                    Some(image) => image.path.clone(),
+                    None => {
+                        // The extension comes from the media type, ex: `image/svg+xml` gives `svg`
+                        let extension: &str = media_type
+                            .trim_start_matches("image/")
+                            .trim_start_matches("x-")
+                            .split('+')
+                            .next()
+                            .unwrap_or_default();
+                        let path: String = format!("images/image-{}.{extension}", images.len() + 1);
+                        images.push(EpubImage { path: path.clone(), media_type: media_type.to_string(), content });
+                        path
+                    }
+                };
+                extracted.push_str(&path);
+            }
+            None => extracted.push_str(data_uri),
+        }
+        rest = &after[data_uri_end..];
+    }
+
+    extracted.push_str(rest);
+    extracted
+}
+
+/// Wraps a body in an XHTML content document of the book.
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{EPUB_LANGUAGE}\" xml:lang=\"{EPUB_LANGUAGE}\">\n\
+         <head><meta charset=\"UTF-8\"/><title>{}</title><link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/></head>\n\
+         <body>{body}</body>\n</html>\n",
+        escape_html(title)
+    )
+}
+
+/// Builds the body of the navigation document: the table of contents of the book.
+fn navigation_body(root: &DocNode, anchors: &HashMap<String, String>) -> String {
+    format!(
+        "<nav epub:type=\"toc\" id=\"toc\"><h1>Table of contents</h1>{}</nav>",
+        navigation_items(root, anchors)
+    )
+}
+
+/// Lists the children of a node in the table of contents, with their own children, recursively.
+fn navigation_items(node: &DocNode, anchors: &HashMap<String, String>) -> String {
+    let mut html: String = String::from("<ol>");
+
+    for child in node.sorted_children() {
+        let id: String = child.id();
+        html.push_str(&format!(
+            "<li><a href=\"{}#{}\">{}</a>",
+            escape_html(anchors.get(&id).map(String::as_str).unwrap_or_default()),
+            escape_html(&id),
+            escape_html(&child.title)
+        ));
+        if !child.children.is_empty() {
+            html.push_str(&navigation_items(child, anchors));
+        }
+        html.push_str("</li>");
+    }
+
+    html.push_str("</ol>");
+    html
+}
+
+/// Builds the body of the title page: the name, the description and the commit of the repository.
+fn title_body(title: &str, repository: &Repository) -> String {
+    let mut html: String = format!("<h1>{}</h1>", escape_html(title));
+    if !repository.description.is_empty() {
+        html.push_str(&format!("<p>{}</p>", escape_html(&repository.description)));
+    }
+    if !repository.commit_hash.is_empty() {
+        html.push_str(&format!("<p>Generated from the commit <code>{}</code>.</p>", escape_html(&repository.commit_hash)));
+    }
+    html
+}
+
+/// Builds the package document: the metadata, the files and the reading order of the book.
+fn package_document(title: &str, repository: &Repository, chapters: &[(String, String)], images: &[EpubImage]) -> String {
+    let mut metadata: String = format!(
+        "<dc:identifier id=\"book-id\">urn:crabodex:{}:{}</dc:identifier><dc:title>{}</dc:title><dc:language>{EPUB_LANGUAGE}</dc:language>",
+        escape_html(&slugify(title)),
+        escape_html(&repository.commit_hash),
+        escape_html(title)
+    );
+    if !repository.description.is_empty() {
+        metadata.push_str(&format!("<dc:description>{}</dc:description>", escape_html(&repository.description)));
+    }
+    if !repository.url.is_empty() {
+        metadata.push_str(&format!("<dc:source>{}</dc:source>", escape_html(&repository.url)));
+    }
+    metadata.push_str(&format!(
+        "<meta property=\"dcterms:modified\">{}</meta>",
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    ));
+
+    let mut manifest: String = String::from(
+        "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\
+         <item id=\"title\" href=\"title.xhtml\" media-type=\"application/xhtml+xml\"/>\
+         <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>",
+    );
+    let mut spine: String = String::from("<itemref idref=\"title\"/><itemref idref=\"nav\"/>");
+    for (index, (file_name, chapter)) in chapters.iter().enumerate() {
+        // The reading systems need to know which chapters have formulas, drawings or remote images
+        let properties: Vec<&str> = [("<math", "mathml"), ("<svg", "svg"), ("src=\"http", "remote-resources")]
+            .into_iter()
+            .filter(|(tag, _)| chapter.contains(tag))
+            .map(|(_, property)| property)
+            .collect();
+        let properties: String = if properties.is_empty() {
+            String::default()
+        } else {
+            format!(" properties=\"{}\"", properties.join(" "))
+        };
+        manifest.push_str(&format!(
+            "<item id=\"chapter-{}\" href=\"{file_name}\" media-type=\"application/xhtml+xml\"{properties}/>",
+            index + 1
+        ));
+        spine.push_str(&format!("<itemref idref=\"chapter-{}\"/>", index + 1));
+    }
+    for (index, image) in images.iter().enumerate() {
+        manifest.push_str(&format!(
+            "<item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>",
+            index + 1,
+            escape_html(&image.path),
+            escape_html(&image.media_type)
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" xml:lang=\"{EPUB_LANGUAGE}\">\n\
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">{metadata}</metadata>\n\
+         <manifest>{manifest}</manifest>\n\
+         <spine>{spine}</spine>\n\
+         </package>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_epub() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        pricing.position = Some(1);
+        pricing.content = Some("See the [refunds](#refunds).".to_string());
+        let mut refunds: DocNode = DocNode::new("Refunds", "Refunds");
+        refunds.position = Some(2);
+        refunds.content = Some("Two weeks.".to_string());
+        root.children.insert("Pricing".to_string(), pricing);
+        root.children.insert("Refunds".to_string(), refunds);
+
+        let repository: Repository = Repository {
+            name: "Billing rules".to_string(),
+            description: "The rules of the billing.".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            ..Repository::default()
+        };
+        let files: Vec<(PathBuf, Vec<u8>)> = export_epub(&root, Path::new(""), &repository, &Config::default(), &mut Vec::new());
+        assert_eq!(files[0].0, PathBuf::from("billing-rules.epub"));
+
+        // The media type is the first file, stored without compression
+        assert_eq!(&files[0].1[30..58], b"mimetypeapplication/epub+zip");
+        let book: HashMap<String, String> = stored_files(&files[0].1);
+        assert!(book["OEBPS/content.opf"].contains("<dc:identifier id=\"book-id\">urn:crabodex:billing-rules:ebb34e7</dc:identifier>"));
+        assert!(book["OEBPS/nav.xhtml"].contains("<li><a href=\"chapter-1.xhtml#pricing\">Pricing</a></li><li><a href=\"chapter-2.xhtml#refunds\">Refunds</a></li>"));
+        assert!(book["OEBPS/chapter-1.xhtml"].contains("href=\"chapter-2.xhtml#refunds\">refunds</a>"));
+    }
+
+    #[test]
+    fn test_export_epub_xhtml() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        pricing.content = Some(
+            "Line<br>break and <img src=\"x.png\">.\n\n<div>\n\n| A | B |\n| - | - |\n| 1 | 2 |\n\n- [x] Done\n\n> [!NOTE]\n> Noted[^1].\n\n$x^2$\n\n```rust\nlet a = 1 < 2;\n```\n\n[^1]: A note."
+                .to_string(),
+        );
+        root.children.insert("Pricing".to_string(), pricing);
+
+        let files: Vec<(PathBuf, Vec<u8>)> = export_epub(&root, Path::new(""), &Repository::default(), &Config::default(), &mut Vec::new());
+
+        let book: HashMap<String, String> = stored_files(&files[0].1);
+        let options: roxmltree::ParsingOptions = roxmltree::ParsingOptions { allow_dtd: true, ..roxmltree::ParsingOptions::default() };
+        for (name, content) in book.iter().filter(|(name, _)| name.ends_with(".xhtml")) {
+            if let Err(error) = roxmltree::Document::parse_with_options(content, options) {
+                panic!("{name} is not well-formed: {error}");
+            }
+        }
+        assert!(book["OEBPS/chapter-1.xhtml"].contains("Line&lt;br&gt;break"));
+    }
+
+    /// Reads the files stored in an archive, from their local headers.
+    fn stored_files(archive: &[u8]) -> HashMap<String, String> {
+        let mut files: HashMap<String, String> = HashMap::new();
+        let mut offset: usize = 0;
+        // The local headers come first, the central directory follows them
+        while archive[offset..offset + 4] == [0x50, 0x4b, 0x03, 0x04] {
+            let read_u16 = |at: usize| usize::from(u16::from_le_bytes([archive[offset + at], archive[offset + at + 1]]));
+            let size: usize = read_u16(18) | (read_u16(20) << 16);
+            let name_start: usize = offset + 30;
+            let content_start: usize = name_start + read_u16(26) + read_u16(28);
+            let name: String = String::from_utf8(archive[name_start..name_start + read_u16(26)].to_vec()).unwrap();
+            files.insert(name, String::from_utf8_lossy(&archive[content_start..content_start + size]).to_string());
+            offset = content_start + size;
+        }
+        files
+    }
+
+    #[test]
+    fn test_extract_images() {
+        let html: &str = "<img src=\"data:image/png;base64,iVBORw==\" alt=\"A\" /><img src=\"data:image/png;base64,iVBORw==\" alt=\"B\" /><img src=\"chart.svg\" alt=\"C\" />";
+        let mut images: Vec<EpubImage> = Vec::new();
+
+        let extracted: String = extract_images(html, &mut images);
+
+        assert_eq!(
+            extracted,
+            "<img src=\"images/image-1.png\" alt=\"A\" /><img src=\"images/image-1.png\" alt=\"B\" /><img src=\"chart.svg\" alt=\"C\" />"
+        );
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].content, vec![0x89, b'P', b'N', b'G']);
+    }
+}
@@ -0,0 +1,205 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::build_page_body::render_content;
+use crate::core::config::Config;
+use crate::core::constants::{JSON_SCHEMA_URL, JSON_SCHEMA_VERSION};
+use crate::core::diagnostic::Diagnostic;
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// The documentation exported as JSON.
+/// - `schema`: The URL of the JSON Schema the document follows.
+/// - `schema_version`: The version of the schema, increased on breaking changes.
+/// - `repository`: The repository the documentation comes from.
+/// - `sections`: The top-level sections of the documentation, in display order.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonDocument {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub schema_version: usize,
+    pub repository: Repository,
+    pub sections: Vec<JsonNode>,
+}
+
+/// A section of the documentation exported as JSON.
+/// - `title`: The title of the section.
+/// - `path`: The path of the section in the Front Matter, from the top-level section.
+/// - `slug`: The anchor of the section in the HTML document.
+/// - `position`: The position of the section among its siblings.
+/// - `depth`: The depth of the section in the tree, 1 for the top-level sections.
+/// - `file_path`: The path of the markdown file of the section, relative to the root directory.
+/// - `start_line`: The first line of the content in the file, numbered from 1.
+/// - `end_line`: The last line of the content in the file, numbered from 1.
+/// - `tags`: The tags of the section.
+/// - `front_matter`: The whole Front Matter of the file.
+/// - `markdown`: The markdown content of the section, with its includes resolved.
+/// - `html`: The content of the section rendered as in the HTML page.
+/// - `repository`: The repository the section comes from, when it is not the one of the documentation.
+/// - `children`: The subsections, in display order.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonNode {
+    pub title: String,
+    pub path: Vec<String>,
+    pub slug: String,
+    pub position: Option<usize>,
+    pub depth: usize,
+    pub file_path: Option<String>,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub tags: Vec<String>,
+    pub front_matter: Option<serde_json::Value>,
+    pub markdown: Option<String>,
+    pub html: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<Repository>,
+    pub children: Vec<JsonNode>,
+}
+
+/// Exports the documentation tree as JSON.
+///
+/// The sections come in the order of the navigation.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `repository` - The repository the documentation comes from.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while rendering the content are added to.
+///
+/// # Returns
+/// The documentation as a JSON string.
+///
+/// # Panics
+/// If the document cannot be serialized.
+///
+#[must_use]
+pub fn export_json(
+    root: &DocNode,
+    root_directory: &Path,
+    repository: Repository,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    let repository_link: (&str, &str) = (&repository.url, &repository.source_ref);
+    let sections: Vec<JsonNode> = root
+        .sorted_children()
+        .into_iter()
+        .map(|child| json_node(child, &[], root_directory, repository_link, config, diagnostics))
+        .collect();
+
+    let document: JsonDocument = JsonDocument {
+        schema: JSON_SCHEMA_URL.to_string(),
+        schema_version: JSON_SCHEMA_VERSION,
+        repository,
+        sections,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Converts a node and its children to JSON recursively.
+fn json_node(
+    node: &DocNode,
+    parent_path: &[String],
+    root_directory: &Path,
+    repository: (&str, &str),
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> JsonNode {
+    let mut path: Vec<String> = parent_path.to_vec();
+    path.push(node.path.rsplit(" > ").next().unwrap_or_default().to_string());
+    let depth: usize = path.len();
+    // The repository of the node is only exported where it differs from the one of its parent
+    let node_repository: Option<&Repository> = node
+        .repository
+        .as_ref()
+        .filter(|node_repository| (node_repository.url.as_str(), node_repository.source_ref.as_str()) != repository);
+    let repository: (&str, &str) = match node_repository {
+        Some(node_repository) => (&node_repository.url, &node_repository.source_ref),
+        None => repository,
+    };
+    // The links to the imported sections follow the format of their forge
+    let link_config: Option<Config> = node_repository.map(|node_repository| node_repository.link_config(config));
+    let config: &Config = link_config.as_ref().unwrap_or(config);
+
+    let html: Option<String> = node
+        .content
+        .as_deref()
+        .map(|content| render_content(node, content, root_directory, depth, repository, config, diagnostics));
+    // The Front Matter is exported as it is written, whatever its fields
+    let front_matter: Option<serde_json::Value> = node
+        .front_matter
+        .as_deref()
+        .and_then(|front_matter| serde_yaml::from_str(front_matter).ok());
+    let children: Vec<JsonNode> = node
+        .sorted_children()
+        .into_iter()
+        .map(|child| json_node(child, &path, root_directory, repository, config, diagnostics))
+        .collect();
+
+    JsonNode {
+        title: node.title.clone(),
+        slug: node.id(),
+        position: node.position,
+        depth,
+        file_path: node.file_path.clone(),
+        start_line: node.start_line,
+        end_line: node.end_line,
+        tags: node.tags.clone(),
+        front_matter,
+        markdown: node.content.clone(),
+        html,
+        repository: node_repository.cloned(),
+        children,
+        path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::core::build_doc_structure::build_doc_structure;
+    use crate::find_markdown_files;
+
+    use super::*;
+
+    #[test]
+    fn test_export_json() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+
+        let markdown_files: Vec<PathBuf> = find_markdown_files(test_dir.clone(), &["ignored_test_files".to_string()]);
+        let doc_structure: DocNode = build_doc_structure(&markdown_files, test_dir.as_path());
+        let repository: Repository = Repository {
+            name: "Test repository".to_string(),
+            description: String::default(),
+            url: "https://github.com/test_repo".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            source_ref: "ebb34e7".to_string(),
+            ..Repository::default()
+        };
+        let json: String = export_json(&doc_structure, &test_dir, repository, &Config::default(), &mut Vec::new());
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(document["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(document["repository"]["forge"], "github");
+        let titles: Vec<&str> = document["sections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|section| section["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["Getting Started", "Features", "Domain"]);
+
+        let configuration: &serde_json::Value = &document["sections"][0]["children"][0];
+        assert_eq!(configuration["path"], serde_json::json!(["Getting Started", "Configuration"]));
+        assert_eq!(configuration["slug"], "getting-started->-configuration");
+        assert_eq!(configuration["depth"], 2);
+        assert_eq!(configuration["front_matter"]["path"][1], "Configuration");
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Local;
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+use crate::core::collect_headings::{collect_headings, Heading};
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_admonitions::{expand_admonition_containers, replace_admonitions};
+use crate::core::repository::Repository;
+use crate::core::resolve_includes::{follow_transclusion_marker, paired_transclusion_markers, transclusion_marker};
+use crate::core::resolve_relative_path::resolve_relative_path;
+use crate::DocNode;
+
+/// The sectioning commands, by depth in the document.
+const SECTIONING_COMMANDS: &[&str] = &["part", "chapter", "section", "subsection", "subsubsection", "paragraph", "subparagraph"];
+
+/// The languages of the code blocks known to the `listings` package, by fenced language.
+const LISTINGS_LANGUAGES: &[(&str, &str)] = &[
+    ("bash", "bash"),
+    ("c", "C"),
+    ("c++", "C++"),
+    ("cpp", "C++"),
+    ("html", "HTML"),
+    ("java", "Java"),
+    ("php", "PHP"),
+    ("py", "Python"),
+    ("python", "Python"),
+    ("rb", "Ruby"),
+    ("ruby", "Ruby"),
+    ("sh", "bash"),
+    ("sql", "SQL"),
+    ("xml", "XML"),
+];
+
+/// The preamble of the document: its class and the packages the content relies on.
+const PREAMBLE: &str = r"\documentclass[11pt,a4paper,oneside]{report}
+\usepackage[utf8]{inputenc}
+\usepackage[T1]{fontenc}
+\usepackage{lmodern}
+\usepackage{amsmath}
+\usepackage{amssymb}
+\usepackage{graphicx}
+\usepackage{longtable}
+\usepackage{listings}
+\usepackage[normalem]{ulem}
+\usepackage{hyperref}
+\lstset{basicstyle=\ttfamily\small, breaklines=true, frame=single, columns=fullflexible}
+";
+
+/// Exports the documentation as a LaTeX document, ready to be typeset.
+///
+/// The document starts with a title page from the repository and a table of contents. The depth
+/// of the sections gives their sectioning command: `\part` for the top-level ones, then
+/// `\chapter`, `\section` and below, and the headings of their content are demoted below them.
+/// The local images are included relative to the root directory, where the document should be
+/// typeset. The raw HTML can not be typeset, it is left out.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `repository` - The repository the documentation comes from.
+///
+/// # Returns
+/// The documentation as a LaTeX string.
+///
+#[must_use]
+pub fn export_latex(root: &DocNode, repository: &Repository) -> String {
+    let title: &str = if repository.name.is_empty() { "Documentation" } else { &repository.name };
+    let mut latex: String = String::from(PREAMBLE);
+    latex.push_str(&format!("\\hypersetup{{pdftitle={{{}}}}}\n\n\\begin{{document}}\n\n", escape_latex(title)));
+
+    latex.push_str(&format!("\\begin{{titlepage}}\n\\centering\n{{\\Huge\\bfseries {}\\par}}\n", escape_latex(title)));
+    if !repository.description.is_empty() {
+        latex.push_str(&format!("\\vspace{{1cm}}\n{{\\large {}\\par}}\n", escape_latex(&repository.description)));
+    }
+    latex.push_str("\\vfill\n");
+    if !repository.commit_hash.is_empty() {
+        latex.push_str(&format!("{{Generated from the commit \\texttt{{{}}}", escape_latex(&repository.commit_hash)));
+        if !repository.url.is_empty() {
+            latex.push_str(&format!(" of \\url{{{}}}", escape_url(&repository.url)));
+        }
+        latex.push_str("\\par}\n");
+    }
+    latex.push_str(&format!("{{{}\\par}}\n\\end{{titlepage}}\n\n\\tableofcontents\n\n", Local::now().format("%Y-%m-%d")));
+
+    push_sections(root, 1, &mut latex);
+    latex.push_str("\\end{document}\n");
+    latex
+}
+
+/// Adds the children of a node to the document, with their own children, recursively.
+fn push_sections(node: &DocNode, depth: usize, latex: &mut String) {
+    for child in node.sorted_children() {
+        latex.push_str(&format!(
+            "\\{}{{{}}}\\label{{{}}}\n\n",
+            sectioning_command(depth),
+            escape_latex(&child.title),
+            latex_label(&child.id())
+        ));
+        if let Some(content) = &child.content {
+            latex.push_str(&render_latex(child, content, depth));
+        }
+        push_sections(child, depth + 1, latex);
+    }
+}
+
+/// The sectioning command of a depth, the deepest one beyond the last.
+fn sectioning_command(depth: usize) -> &'static str {
+    SECTIONING_COMMANDS[depth.clamp(1, SECTIONING_COMMANDS.len()) - 1]
+}
+
+/// Turns an anchor into a label: the characters other than the alphanumerics and the dashes are replaced.
+fn latex_label(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { ':' })
+        .collect()
+}
+
+/// Escapes the characters with a special meaning in LaTeX.
+fn escape_latex(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the characters of a URL with a special meaning in the `\href` and `\url` commands.
+fn escape_url(url: &str) -> String {
+    url.replace('\\', "/").replace('%', "\\%").replace('#', "\\#").replace(['{', '}'], "")
+}
+
+/// Renders the markdown content of a node as LaTeX.
+#[allow(clippy::too_many_lines)]
+fn render_latex(node: &DocNode, content: &str, depth: usize) -> String {
+    let content: String = expand_admonition_containers(content);
+    let events: Vec<Event> = Parser::new_ext(&content, markdown_options()).collect();
+    let transclusion_markers: HashSet<usize> = paired_transclusion_markers(&events);
+    // The raw HTML can not be typeset, only the markers of the included files are kept to resolve their images,
+    // the admonitions are then replaced with raw LaTeX
+    let events: Vec<Event> = events
+        .into_iter()
+        .enumerate()
+        .filter(|(index, event)| !matches!(event, Event::Html(_) | Event::InlineHtml(_)) || transclusion_markers.contains(index))
+        .map(|(_, event)| event)
+        .collect();
+    let events: Vec<Event> = replace_admonitions(
+        events,
+        |_, title| format!("\\begin{{quote}}\n\\textbf{{{}}}\\par\n", escape_latex(title)),
+        "\\end{quote}\n\n",
+    );
+
+    // The headings come in the same order as the events, with their anchors
+    let mut headings: std::vec::IntoIter<Heading> = collect_headings(node).into_iter();
+    let mut heading_label: Option<String> = None;
+    let mut latex: String = String::with_capacity(content.len() * 3 / 2);
+    // The footnotes are written where they are referenced, their definitions are set aside
+    let mut footnotes: HashMap<String, String> = HashMap::new();
+    let mut footnote: Option<(String, String)> = None;
+    let mut lists: Vec<bool> = Vec::new();
+    let mut image: Option<(String, String)> = None;
+    let mut in_code_block: bool = false;
+    let mut in_metadata_block: bool = false;
+    let mut first_cell: bool = false;
+    // The file the current content comes from, the last included one or the file of the node
+    let mut file_paths: Vec<String> = node.file_path.iter().cloned().collect();
+
+    for event in events {
+        let output: &mut String = match &mut footnote {
+            Some((_, definition)) => definition,
+            None => &mut latex,
+        };
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => in_metadata_block = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_metadata_block = false,
+            _ if in_metadata_block => {}
+            Event::Start(Tag::Image { dest_url, .. }) => image = Some((dest_url.to_string(), String::default())),
+            Event::End(TagEnd::Image) => {
+                if let Some((url, alt)) = image.take() {
+                    output.push_str(&latex_image(file_paths.last().map(String::as_str), &url, &alt));
+                }
+            }
+            Event::Text(text) | Event::Code(text) if image.is_some() => {
+                if let Some((_, alt)) = &mut image {
+                    alt.push_str(&text);
+                }
+            }
+            _ if image.is_some() => {}
+            Event::Text(text) if in_code_block => output.push_str(&text),
+            Event::Text(text) => output.push_str(&escape_latex(&text)),
+            Event::Code(code) => output.push_str(&format!("\\texttt{{{}}}", escape_latex(&code))),
+            Event::InlineMath(tex) => output.push_str(&format!("${tex}$")),
+            Event::DisplayMath(tex) => output.push_str(&format!("\\[{tex}\\]")),
+            Event::Html(raw) if transclusion_marker(&raw).is_some() => {
+                if let Some(marker) = transclusion_marker(&raw) {
+                    follow_transclusion_marker(&mut file_paths, &marker);
+                }
+            }
+            Event::Html(latex_markup) => output.push_str(&latex_markup),
+            Event::SoftBreak => output.push('\n'),
+            Event::HardBreak => output.push_str("\\\\\n"),
+            Event::Rule => output.push_str("\\noindent\\rule{\\linewidth}{0.4pt}\n\n"),
+            Event::TaskListMarker(checked) => output.push_str(if checked { "$\\boxtimes$ " } else { "$\\square$ " }),
+            Event::FootnoteReference(label) => output.push_str(&format!("\u{0}{label}\u{0}")),
+            Event::Start(Tag::FootnoteDefinition(label)) => footnote = Some((label.to_string(), String::default())),
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if let Some((label, definition)) = footnote.take() {
+                    footnotes.insert(label, definition.trim().to_string());
+                }
+            }
+            Event::Start(Tag::Heading { level, .. }) => {
+                heading_label = headings.next().map(|heading| latex_label(&heading.id));
+                output.push_str(&format!("\\{}{{", sectioning_command(level as usize + depth)));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                match heading_label.take() {
+                    Some(label) => output.push_str(&format!("}}\\label{{{label}}}\n\n")),
+                    None => output.push_str("}\n\n"),
+                }
+            }
+            Event::End(TagEnd::Paragraph) => output.push_str("\n\n"),
+            Event::Start(Tag::Emphasis) => output.push_str("\\emph{"),
+            Event::Start(Tag::Strong) => output.push_str("\\textbf{"),
+            Event::Start(Tag::Strikethrough) => output.push_str("\\sout{"),
+            Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link) => output.push('}'),
+            Event::Start(Tag::Link { dest_url, .. }) => match dest_url.strip_prefix('#') {
+                Some(anchor) => output.push_str(&format!("\\hyperref[{}]{{", latex_label(anchor))),
+                None => output.push_str(&format!("\\href{{{}}}{{", escape_url(&dest_url))),
+            },
+            Event::Start(Tag::BlockQuote(_)) => output.push_str("\\begin{quote}\n"),
+            Event::End(TagEnd::BlockQuote(_)) => output.push_str("\\end{quote}\n\n"),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                let language: String = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_lowercase(),
+                    CodeBlockKind::Indented => String::default(),
+                };
+                match LISTINGS_LANGUAGES.iter().find(|(fenced, _)| *fenced == language) {
+                    Some((_, listings_language)) => output.push_str(&format!("\\begin{{lstlisting}}[language={listings_language}]\n")),
+                    None => output.push_str("\\begin{lstlisting}\n"),
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                if !output.ends_with('\n') {
+                    output.push('\n');
+                }
+                output.push_str("\\end{lstlisting}\n\n");
+            }
+            Event::Start(Tag::List(start)) => {
+                lists.push(start.is_some());
+                match start {
+                    Some(1) => output.push_str("\\begin{enumerate}\n"),
+                    Some(start) => output.push_str(&format!("\\begin{{enumerate}}\\setcounter{{enumi}}{{{}}}\n", start.saturating_sub(1))),
+                    None => output.push_str("\\begin{itemize}\n"),
+                }
+            }
+            Event::End(TagEnd::List(_)) => {
+                let ordered: bool = lists.pop().unwrap_or_default();
+                output.push_str(if ordered { "\\end{enumerate}\n\n" } else { "\\end{itemize}\n\n" });
+            }
+            Event::Start(Tag::Item) => output.push_str("\\item "),
+            Event::Start(Tag::Table(alignments)) => {
+                let columns: String = alignments
+                    .iter()
+                    .map(|alignment| match alignment {
+                        Alignment::Center => 'c',
+                        Alignment::Right => 'r',
+                        Alignment::Left | Alignment::None => 'l',
+                    })
+                    .collect();
+                output.push_str(&format!("\\begin{{longtable}}{{{columns}}}\n\\hline\n"));
+            }
+            Event::End(TagEnd::Table) => output.push_str("\\hline\n\\end{longtable}\n\n"),
+            Event::Start(Tag::TableHead | Tag::TableRow) => first_cell = true,
+            Event::End(TagEnd::TableHead) => output.push_str(" \\\\\n\\hline\n\\endhead\n"),
+            Event::End(TagEnd::TableRow) => output.push_str(" \\\\\n"),
+            Event::Start(Tag::TableCell) => {
+                output.push_str(if first_cell { "" } else { " & " });
+                first_cell = false;
+            }
+            Event::Start(Tag::DefinitionList) => output.push_str("\\begin{description}\n"),
+            Event::End(TagEnd::DefinitionList) => output.push_str("\\end{description}\n\n"),
+            Event::Start(Tag::DefinitionListTitle) => output.push_str("\\item["),
+            Event::End(TagEnd::DefinitionListTitle) => output.push_str("] "),
+            _ => {}
+        }
+    }
+
+    // The references are replaced with the footnotes, now that all the definitions are known
+    for (label, definition) in footnotes {
+        latex = latex.replace(&format!("\u{0}{label}\u{0}"), &format!("\\footnote{{{definition}}}"));
+    }
+    latex.replace('\u{0}', "")
+}
+
+/// Includes an image: the local ones from their path in the root directory, the others as links.
+///
+/// The local images are resolved against the file they are written in, the node's or an included one.
+fn latex_image(file_path: Option<&str>, url: &str, alt: &str) -> String {
+    let has_scheme: bool = url
+        .find(':')
+        .is_some_and(|index| !url[..index].contains(['/', '?', '#']));
+    let path: Option<String> = file_path
+        .filter(|_| !has_scheme && !url.starts_with('/'))
+        .and_then(|file_path| resolve_relative_path(file_path, url.split(['?', '#']).next().unwrap_or_default()))
+        .map(|path| path.to_string_lossy().replace('\\', "/"));
+
+    match path {
+        Some(path) => format!("\\includegraphics[width=\\linewidth,keepaspectratio]{{{}}}", escape_url(&path)),
+        None => format!("\\href{{{}}}{{{}}}", escape_url(url), escape_latex(alt)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_latex() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        let mut discounts: DocNode = DocNode::new("Discounts", "Pricing > Discounts");
+        discounts.file_path = Some("pricing/discounts.md".to_string());
+        discounts.content = Some(
+            "# Stacking\n\nA 10% *discount*[^1], see [refunds](#refunds).\n\n- [x] Done\n\n| Plan | Price |\n| :--- | ---: |\n| Pro | $10 |\n\n```python\nprint(1)\n```\n\n$$a^2$$\n\n![Chart](chart.png)\n\n[^1]: Never stacked."
+                .to_string(),
+        );
+        pricing.children.insert("Discounts".to_string(), discounts);
+        root.children.insert("Pricing".to_string(), pricing);
+
+        let repository: Repository = Repository {
+            name: "Billing".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            ..Repository::default()
+        };
+        let latex: String = export_latex(&root, &repository);
+
+        assert!(latex.contains("{\\Huge\\bfseries Billing\\par}"));
+        assert!(latex.contains("Generated from the commit \\texttt{ebb34e7}"));
+        assert!(latex.contains("\\tableofcontents"));
+        assert!(latex.contains("\\part{Pricing}\\label{pricing}"));
+        assert!(latex.contains("\\chapter{Discounts}\\label{pricing-:-discounts}"));
+        assert!(latex.contains("\\section{Stacking}"));
+        assert!(latex.contains("A 10\\% \\emph{discount}\\footnote{Never stacked.}, see \\hyperref[refunds]{refunds}."));
+        assert!(latex.contains("\\item $\\boxtimes$ Done"));
+        assert!(latex.contains("\\begin{longtable}{lr}\n\\hline\nPlan & Price \\\\\n\\hline\n\\endhead\nPro & \\$10 \\\\\n\\hline\n\\end{longtable}"));
+        assert!(latex.contains("\\begin{lstlisting}[language=Python]\nprint(1)\n\\end{lstlisting}"));
+        assert!(latex.contains("\\[a^2\\]"));
+        assert!(latex.contains("\\includegraphics[width=\\linewidth,keepaspectratio]{pricing/chart.png}"));
+        assert!(latex.ends_with("\\end{document}\n"));
+    }
+
+    #[test]
+    fn test_export_latex_transclusion_images() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut discounts: DocNode = DocNode::new("Discounts", "Discounts");
+        discounts.file_path = Some("pricing/discounts.md".to_string());
+        discounts.content = Some(
+            "<!-- crabodex:transclusion-start shared/legal.md -->\n\n![Seal](images/seal.png)\n\n<!-- crabodex:transclusion-end -->\n\n![Chart](chart.png)\n\n<!-- crabodex:transclusion-end -->"
+                .to_string(),
+        );
+        root.children.insert("Discounts".to_string(), discounts);
+
+        let latex: String = export_latex(&root, &Repository::default());
+
+        assert!(latex.contains("\\includegraphics[width=\\linewidth,keepaspectratio]{shared/images/seal.png}"));
+        assert!(latex.contains("\\includegraphics[width=\\linewidth,keepaspectratio]{pricing/chart.png}"));
+        assert!(!latex.contains("crabodex:transclusion"));
+    }
+}
@@ -0,0 +1,276 @@
+use std::path::PathBuf;
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::core::build_source_link::build_source_link;
+use crate::core::config::Config;
+use crate::core::constants::{LLMS_FULL_TEXT_PATH, LLMS_INDEX_PATH};
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_admonitions::expand_admonition_containers;
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// Exports the documentation as an `llms.txt` index and a full plain-text rendition.
+///
+/// The index lists the sections under their top-level section, in display order, each one with
+/// a link to its source file and a one-line summary: the `description` of its Front Matter, or
+/// the first paragraph of its content. The full text has the content of every section without
+/// its markup, under its path.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `repository` - The repository the documentation comes from.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// The index and the full text relative to the output directory, with their content.
+///
+#[must_use]
+pub fn export_llms_text(root: &DocNode, repository: &Repository, config: &Config) -> Vec<(PathBuf, Vec<u8>)> {
+    vec![
+        (PathBuf::from(LLMS_INDEX_PATH), llms_index(root, repository, config).into_bytes()),
+        (PathBuf::from(LLMS_FULL_TEXT_PATH), llms_full_text(root, repository).into_bytes()),
+    ]
+}
+
+/// Builds the `llms.txt` index: the title, the summary and the list of the sections.
+fn llms_index(root: &DocNode, repository: &Repository, config: &Config) -> String {
+    let title: &str = if repository.name.is_empty() { "Documentation" } else { &repository.name };
+    let mut index: String = format!("# {title}\n\n");
+    if !repository.description.is_empty() {
+        index.push_str(&format!("> {}\n\n", repository.description));
+    }
+    if !repository.commit_hash.is_empty() {
+        index.push_str(&format!("Generated from the commit {}. ", repository.commit_hash));
+    }
+    index.push_str(&format!("The whole documentation is in plain text in [{LLMS_FULL_TEXT_PATH}]({LLMS_FULL_TEXT_PATH}).\n"));
+
+    for child in root.sorted_children() {
+        index.push_str(&format!("\n## {}\n\n", child.title));
+        push_index_entries(child, &[], repository, config, &mut index);
+    }
+
+    index
+}
+
+/// Adds the entries of a node and of its children to the index, recursively.
+fn push_index_entries(node: &DocNode, parents: &[&str], repository: &Repository, config: &Config, index: &mut String) {
+    let mut titles: Vec<&str> = parents.to_vec();
+    titles.push(&node.title);
+    let title: String = titles.join(" > ");
+
+    let entry: String = match source_link(node, repository, config) {
+        Some(link) => format!("- [{title}]({link})"),
+        None => format!("- {title}"),
+    };
+    match summary(node) {
+        Some(summary) => index.push_str(&format!("{entry}: {summary}\n")),
+        None => index.push_str(&format!("{entry}\n")),
+    }
+
+    for child in node.sorted_children() {
+        push_index_entries(child, &titles, repository, config, index);
+    }
+}
+
+/// The link to the source file of a node, in the repository it comes from.
+fn source_link(node: &DocNode, repository: &Repository, config: &Config) -> Option<String> {
+    let file_path: &str = node.file_path.as_deref()?;
+    let repository: &Repository = node.repository.as_ref().unwrap_or(repository);
+    // Without a repository or a ref to link to, the path of the file is all there is
+    if repository.url.is_empty() {
+        return Some(file_path.to_string());
+    }
+    build_source_link(&repository.url, &repository.source_ref, file_path, None, &repository.link_config(config))
+        .or_else(|| Some(file_path.to_string()))
+}
+
+/// The one-line summary of a node: its description, or the first paragraph of its content.
+fn summary(node: &DocNode) -> Option<String> {
+    node.description.clone().or_else(|| node.content.as_deref().and_then(first_paragraph))
+}
+
+/// Extracts the text of the first paragraph of a markdown content, on one line.
+fn first_paragraph(content: &str) -> Option<String> {
+    let content: String = expand_admonition_containers(content);
+    let mut paragraph: Option<String> = None;
+
+    for event in Parser::new_ext(&content, markdown_options()) {
+        match event {
+            Event::Start(Tag::Paragraph) => paragraph = Some(String::default()),
+            Event::Text(fragment) | Event::Code(fragment) => {
+                if let Some(paragraph) = &mut paragraph {
+                    paragraph.push_str(&fragment);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(paragraph) = &mut paragraph {
+                    paragraph.push(' ');
+                }
+            }
+            Event::End(TagEnd::Paragraph) => {
+                let text: String = paragraph.take().unwrap_or_default().split_whitespace().collect::<Vec<&str>>().join(" ");
+                if !text.is_empty() {
+                    return Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Builds the full plain-text rendition: every section under its underlined path, in display order.
+fn llms_full_text(root: &DocNode, repository: &Repository) -> String {
+    let mut text: String = String::default();
+    if !repository.name.is_empty() {
+        text.push_str(&underline(&repository.name, '='));
+    }
+    if !repository.description.is_empty() {
+        text.push_str(&format!("{}\n\n", repository.description));
+    }
+    push_full_text(root, &[], &mut text);
+
+    format!("{}\n", text.trim_end())
+}
+
+/// Adds the text of the children of a node to the full text, with their own children, recursively.
+fn push_full_text(node: &DocNode, parents: &[&str], text: &mut String) {
+    for child in node.sorted_children() {
+        let mut titles: Vec<&str> = parents.to_vec();
+        titles.push(&child.title);
+        text.push_str(&underline(&titles.join(" > "), if parents.is_empty() { '=' } else { '-' }));
+
+        let content: String = child.content.as_deref().map(plain_text).unwrap_or_default();
+        if !content.is_empty() {
+            text.push_str(&content);
+            text.push_str("\n\n");
+        }
+
+        push_full_text(child, &titles, text);
+    }
+}
+
+/// Underlines a title, followed by a blank line.
+fn underline(title: &str, character: char) -> String {
+    format!("{title}\n{}\n\n", character.to_string().repeat(title.chars().count()))
+}
+
+/// Renders a markdown content as plain text, keeping its blocks, lists, tables and code.
+fn plain_text(content: &str) -> String {
+    let content: String = expand_admonition_containers(content);
+    let mut text: String = String::with_capacity(content.len());
+    // The next number of each nested list, `None` for the bullet lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    // The destinations of the links being rendered, written after their text
+    let mut links: Vec<String> = Vec::new();
+    let mut in_code_block: bool = false;
+    let mut first_cell: bool = false;
+
+    for event in Parser::new_ext(&content, markdown_options()) {
+        match event {
+            Event::Start(Tag::List(start)) => lists.push(start),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                text.push_str(if lists.is_empty() { "\n\n" } else { "" });
+            }
+            Event::Start(Tag::Item) => {
+                let indent: String = "  ".repeat(lists.len().saturating_sub(1));
+                let marker: String = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                text.push_str(&format!("\n{indent}{marker}"));
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                text.push('\n');
+            }
+            Event::Text(fragment) if in_code_block => {
+                for line in fragment.lines() {
+                    text.push_str(&format!("    {line}\n"));
+                }
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => links.push(dest_url.to_string()),
+            Event::End(TagEnd::Link) => {
+                // The anchors of the HTML document mean nothing in plain text
+                if let Some(destination) = links.pop().filter(|destination| !destination.starts_with('#')) {
+                    text.push_str(&format!(" ({destination})"));
+                }
+            }
+            Event::Start(Tag::TableHead | Tag::TableRow) => first_cell = true,
+            Event::Start(Tag::TableCell) => {
+                text.push_str(if first_cell { "" } else { " | " });
+                first_cell = false;
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => text.push_str(&format!("[{label}]: ")),
+            Event::FootnoteReference(label) => text.push_str(&format!("[{label}]")),
+            Event::TaskListMarker(checked) => text.push_str(if checked { "[x] " } else { "[ ] " }),
+            Event::Text(fragment) | Event::Code(fragment) | Event::InlineMath(fragment) | Event::DisplayMath(fragment) => {
+                text.push_str(&fragment);
+            }
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak | Event::End(TagEnd::TableHead | TagEnd::TableRow | TagEnd::DefinitionListTitle) => text.push('\n'),
+            Event::Rule => text.push_str("----\n\n"),
+            Event::End(TagEnd::Paragraph) if !lists.is_empty() => {}
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Table | TagEnd::DefinitionListDefinition) => text.push_str("\n\n"),
+            _ => {}
+        }
+    }
+
+    // The blocks are separated by a single blank line, without trailing whitespace
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if !(line.is_empty() && matches!(lines.last(), None | Some(&""))) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_llms_text() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        pricing.file_path = Some("pricing.md".to_string());
+        pricing.content = Some("The prices are\ncomputed [daily](#daily).\n\n1. Base\n2. Discounts\n\n```rs\nlet price = 2;\n```\n\n| Plan | Price |\n| --- | --- |\n| Pro | 10 |".to_string());
+        let mut discounts: DocNode = DocNode::new("Discounts", "Pricing > Discounts");
+        discounts.description = Some("The discounts of the loyal customers.".to_string());
+        discounts.content = Some("Some discounts.".to_string());
+        pricing.children.insert("Discounts".to_string(), discounts);
+        root.children.insert("Pricing".to_string(), pricing);
+
+        let repository: Repository = Repository {
+            name: "Billing".to_string(),
+            url: "https://github.com/acme/billing".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            source_ref: "ebb34e7".to_string(),
+            ..Repository::default()
+        };
+        let files: Vec<(PathBuf, Vec<u8>)> = export_llms_text(&root, &repository, &Config::default());
+        let index: String = String::from_utf8(files[0].1.clone()).unwrap();
+        let full_text: String = String::from_utf8(files[1].1.clone()).unwrap();
+
+        assert!(index.starts_with("# Billing\n\nGenerated from the commit ebb34e7."));
+        assert!(index.contains("\n## Pricing\n\n- [Pricing](https://github.com/acme/billing/blob/ebb34e7/pricing.md): The prices are computed daily.\n- Pricing > Discounts: The discounts of the loyal customers.\n"));
+        assert_eq!(
+            full_text,
+            "Billing\n=======\n\n\
+             Pricing\n=======\n\n\
+             The prices are computed daily.\n\n\
+             1. Base\n2. Discounts\n\n    let price = 2;\n\n\
+             Plan | Price\nPro | 10\n\n\
+             Pricing > Discounts\n-------------------\n\n\
+             Some discounts.\n"
+        );
+    }
+}
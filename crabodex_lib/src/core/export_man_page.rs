@@ -0,0 +1,364 @@
+use chrono::Local;
+use pulldown_cmark::{Alignment, Event, HeadingLevel, Parser, Tag, TagEnd};
+
+use crate::core::collect_headings::slugify;
+use crate::core::config::Config;
+use crate::core::diagnostic::Diagnostic;
+use crate::core::markdown_options::markdown_options;
+use crate::core::render_admonitions::{expand_admonition_containers, replace_admonitions};
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// Exports a section of the documentation and its subsections as a roff man page.
+///
+/// The section is the one at the `man_path` of the configuration, the whole documentation if it
+/// is not set. Its subsections are the `.SH` sections of the page, their own subsections the
+/// `.SS` ones, and the headings of their content follow the same levels. The content before the
+/// first heading of the section is its `DESCRIPTION`. The raw HTML is left out.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `repository` - The repository the documentation comes from.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The warnings raised during the generation, a missing section is added to it.
+///
+/// # Returns
+/// The man page as a string, empty if there is no section at the path.
+///
+#[must_use]
+pub fn export_man_page(root: &DocNode, repository: &Repository, config: &Config, diagnostics: &mut Vec<Diagnostic>) -> String {
+    let node: &DocNode = match config.man_path.as_deref() {
+        Some(path) => {
+            let path: String = path.split('>').map(str::trim).collect::<Vec<&str>>().join(" > ");
+            let Some(node) = find_node(root, &path) else {
+                diagnostics.push(Diagnostic::new(None, &format!("No section at the path `{path}` for the man page")));
+                return String::default();
+            };
+            node
+        }
+        None => root,
+    };
+
+    let title: &str = match (node.path.is_empty(), repository.name.is_empty()) {
+        (false, _) => &node.title,
+        (true, false) => &repository.name,
+        (true, true) => "Documentation",
+    };
+    let name: String = slugify(title);
+    let source: String = [repository.name.as_str(), repository.commit_hash.as_str()]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    // The first line asks `man` to run the tables through `tbl`
+    let mut page: String = String::from("'\\\" t\n");
+    page.push_str(&format!(
+        ".TH \"{}\" \"{}\" \"{}\" \"{}\" \"{}\"\n",
+        escape_roff(&name.to_uppercase()),
+        config.man_section,
+        Local::now().format("%Y-%m-%d"),
+        escape_roff(&source),
+        escape_roff(if repository.name.is_empty() { title } else { &repository.name })
+    ));
+
+    let summary: Option<&str> = node
+        .description
+        .as_deref()
+        .or_else(|| (node.path.is_empty() && !repository.description.is_empty()).then_some(repository.description.as_str()));
+    match summary {
+        Some(summary) => page.push_str(&format!(".SH NAME\n{} \\- {}\n", escape_roff(&name), escape_roff(summary))),
+        None => page.push_str(&format!(".SH NAME\n{}\n", escape_roff(&name))),
+    }
+
+    if let Some(content) = &node.content {
+        page.push_str(&render_roff(content, 0));
+    }
+    push_sections(node, 1, &mut page);
+
+    page
+}
+
+/// Finds the node at a path in the tree.
+fn find_node<'a>(node: &'a DocNode, path: &str) -> Option<&'a DocNode> {
+    if node.path == path {
+        return Some(node);
+    }
+    node.children.values().find_map(|child| find_node(child, path))
+}
+
+/// Adds the children of a node to the page, with their own children, recursively.
+fn push_sections(node: &DocNode, depth: usize, page: &mut String) {
+    for child in node.sorted_children() {
+        push_heading(page, depth, &child.title);
+        if let Some(content) = &child.content {
+            page.push_str(&render_roff(content, depth));
+        }
+        push_sections(child, depth + 1, page);
+    }
+}
+
+/// Adds a heading to the page: a `.SH` section at the first level, a `.SS` one at the second,
+/// and a bold paragraph below.
+fn push_heading(page: &mut String, level: usize, title: &str) {
+    match level {
+        0 | 1 => push_request(page, &format!(".SH {}", escape_roff(&title.to_uppercase()))),
+        2 => push_request(page, &format!(".SS {}", escape_roff(title))),
+        _ => push_request(page, &format!(".PP\n\\f[B]{}\\f[R]", escape_roff(title))),
+    }
+}
+
+/// Adds a request to the page, on a line of its own.
+fn push_request(page: &mut String, request: &str) {
+    end_line(page);
+    page.push_str(request);
+    page.push('\n');
+}
+
+/// Ends the current line of the page, if it is not ended yet.
+fn end_line(page: &mut String) {
+    if !page.is_empty() && !page.ends_with('\n') {
+        page.push('\n');
+    }
+}
+
+/// Adds some text to the page, protecting the lines it starts from being read as requests.
+fn push_text(page: &mut String, text: &str) {
+    let mut text: String = escape_roff(text);
+    if page.is_empty() || page.ends_with('\n') {
+        text = text.trim_start().to_string();
+        if text.starts_with(['.', '\'']) {
+            page.push_str("\\&");
+        }
+    }
+    page.push_str(&text);
+}
+
+/// Escapes the characters with a special meaning in roff.
+fn escape_roff(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-").replace('"', "\\(dq")
+}
+
+/// Renders the markdown content of a node at a depth of the page as roff.
+#[allow(clippy::too_many_lines)]
+fn render_roff(content: &str, depth: usize) -> String {
+    let content: String = expand_admonition_containers(content);
+    // The raw HTML can not be rendered, the admonitions are then replaced with raw roff
+    let events: Vec<Event> = Parser::new_ext(&content, markdown_options())
+        .filter(|event| !matches!(event, Event::Html(_) | Event::InlineHtml(_)))
+        .collect();
+    let events: Vec<Event> = replace_admonitions(
+        events,
+        |_, title| format!(".RS 4\n.PP\n\\f[B]{}\\f[R]\n", escape_roff(title)),
+        ".RE\n",
+    );
+
+    let mut roff: String = String::with_capacity(content.len() * 3 / 2);
+    // The content of the section before its first heading is its description
+    let first_block: Option<&Event> = events
+        .iter()
+        .find(|event| matches!(event, Event::Start(tag) if !matches!(tag, Tag::MetadataBlock(_))));
+    if depth == 0 && !matches!(first_block, None | Some(Event::Start(Tag::Heading { level: HeadingLevel::H1, .. }))) {
+        push_request(&mut roff, ".SH DESCRIPTION");
+    }
+
+    // The fonts in use, to restore the enclosing one at the end of an emphasis
+    let mut fonts: Vec<&str> = vec!["R"];
+    // The next number of each nested list, `None` for the bullet lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut links: Vec<String> = Vec::new();
+    let mut heading: Option<(usize, String)> = None;
+    let mut item_start: bool = false;
+    let mut in_code_block: bool = false;
+    let mut in_metadata_block: bool = false;
+    let mut first_cell: bool = false;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => in_metadata_block = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_metadata_block = false,
+            _ if in_metadata_block => {}
+            Event::Start(Tag::Heading { level, .. }) => heading = Some((depth + level as usize, String::default())),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, title)) = heading.take() {
+                    push_heading(&mut roff, level, &title);
+                }
+            }
+            Event::Text(text) | Event::Code(text) if heading.is_some() => {
+                if let Some((_, title)) = &mut heading {
+                    title.push_str(&text);
+                }
+            }
+            _ if heading.is_some() => {}
+            Event::Text(text) if in_code_block => {
+                for line in text.lines() {
+                    let line: String = escape_roff(line);
+                    roff.push_str(if line.starts_with(['.', '\'']) { "\\&" } else { "" });
+                    roff.push_str(&line);
+                    roff.push('\n');
+                }
+            }
+            Event::Text(text) | Event::InlineMath(text) | Event::DisplayMath(text) => {
+                item_start = false;
+                push_text(&mut roff, &text);
+            }
+            Event::Code(code) => {
+                item_start = false;
+                push_text(&mut roff, "");
+                roff.push_str(&format!("\\f[CR]{}\\f[{}]", escape_roff(&code), fonts.last().unwrap_or(&"R")));
+            }
+            Event::Html(roff_markup) => {
+                push_request(&mut roff, roff_markup.trim_end());
+                item_start = false;
+            }
+            Event::HardBreak => push_request(&mut roff, ".br"),
+            Event::Rule => push_request(&mut roff, ".sp"),
+            Event::TaskListMarker(checked) => push_text(&mut roff, if checked { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(label) => push_text(&mut roff, &format!("[{label}]")),
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                push_request(&mut roff, ".PP");
+                push_text(&mut roff, &format!("[{label}] "));
+                item_start = true;
+            }
+            Event::Start(Tag::Paragraph) => {
+                if !item_start {
+                    push_request(&mut roff, if lists.is_empty() { ".PP" } else { ".IP" });
+                }
+                item_start = false;
+            }
+            Event::End(TagEnd::Paragraph | TagEnd::DefinitionListTitle) => end_line(&mut roff),
+            Event::Start(Tag::Emphasis | Tag::Strong) => {
+                let emphasis: &str = if matches!(event, Event::Start(Tag::Emphasis)) { "I" } else { "B" };
+                let font: &str = match (fonts.last().copied(), emphasis) {
+                    (Some("B" | "BI"), "I") | (Some("I" | "BI"), "B") => "BI",
+                    _ => emphasis,
+                };
+                fonts.push(font);
+                roff.push_str(&format!("\\f[{font}]"));
+            }
+            Event::End(TagEnd::Emphasis | TagEnd::Strong) => {
+                fonts.pop();
+                roff.push_str(&format!("\\f[{}]", fonts.last().unwrap_or(&"R")));
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => links.push(dest_url.to_string()),
+            Event::End(TagEnd::Link) => {
+                // The anchors of the HTML document mean nothing in a man page
+                if let Some(destination) = links.pop().filter(|destination| !destination.starts_with('#')) {
+                    push_text(&mut roff, &format!(" <{destination}>"));
+                }
+            }
+            Event::Start(Tag::Image { .. }) => push_text(&mut roff, "["),
+            Event::End(TagEnd::Image) => push_text(&mut roff, "]"),
+            Event::Start(Tag::BlockQuote(_)) => push_request(&mut roff, ".RS 4"),
+            Event::End(TagEnd::BlockQuote(_)) => push_request(&mut roff, ".RE"),
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                push_request(&mut roff, if lists.is_empty() { ".PP\n.RS 4\n.EX" } else { ".IP\n.RS 4\n.EX" });
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                push_request(&mut roff, ".EE\n.RE");
+            }
+            Event::Start(Tag::List(start)) => {
+                if !lists.is_empty() {
+                    push_request(&mut roff, ".RS");
+                }
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if !lists.is_empty() {
+                    push_request(&mut roff, ".RE");
+                }
+            }
+            Event::Start(Tag::Item) => {
+                let request: String = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!(".IP {}. 4", *number - 1)
+                    }
+                    _ => ".IP \\(bu 2".to_string(),
+                };
+                push_request(&mut roff, &request);
+                item_start = true;
+            }
+            Event::Start(Tag::Table(alignments)) => {
+                let columns: Vec<char> = alignments
+                    .iter()
+                    .map(|alignment| match alignment {
+                        Alignment::Center => 'c',
+                        Alignment::Right => 'r',
+                        Alignment::Left | Alignment::None => 'l',
+                    })
+                    .collect();
+                // The header row is bold, the format of the last line applies to the other rows
+                let header: Vec<String> = columns.iter().map(|column| format!("{column}b")).collect();
+                let rows: Vec<String> = columns.iter().map(ToString::to_string).collect();
+                push_request(&mut roff, &format!(".PP\n.TS\nallbox;\n{}\n{}.", header.join(" "), rows.join(" ")));
+            }
+            Event::End(TagEnd::Table) => push_request(&mut roff, ".TE"),
+            Event::Start(Tag::TableHead | Tag::TableRow) => first_cell = true,
+            Event::SoftBreak | Event::End(TagEnd::TableHead | TagEnd::TableRow) => roff.push('\n'),
+            Event::Start(Tag::TableCell) => {
+                roff.push_str(if first_cell { "" } else { "\t" });
+                first_cell = false;
+            }
+            Event::Start(Tag::DefinitionListTitle) => push_request(&mut roff, ".TP"),
+            Event::Start(Tag::DefinitionListDefinition) => item_start = true,
+            _ => {}
+        }
+    }
+
+    roff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_man_page() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut tools: DocNode = DocNode::new("Tools", "Tools");
+        let mut cli: DocNode = DocNode::new("Billing CLI", "Tools > Billing CLI");
+        cli.description = Some("Bill the customers".to_string());
+        cli.content = Some(
+            "Runs the *monthly* billing.\n\n# Options\n\n- `--dry-run`: Prints the bills.\n- `--month`: The month.\n\n```sh\n.hidden\nbilling --dry-run\n```\n\n| Code | Meaning |\n| --- | :---: |\n| 0 | Billed |"
+                .to_string(),
+        );
+        let mut exit_codes: DocNode = DocNode::new("Exit codes", "Tools > Billing CLI > Exit codes");
+        exit_codes.content = Some("## Retries\n\nSee [the runbook](https://runbook.acme.com).".to_string());
+        cli.children.insert("Exit codes".to_string(), exit_codes);
+        tools.children.insert("Billing CLI".to_string(), cli);
+        root.children.insert("Tools".to_string(), tools);
+
+        let repository: Repository = Repository {
+            name: "Billing".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            ..Repository::default()
+        };
+        let config: Config = Config {
+            man_path: Some("Tools>Billing CLI".to_string()),
+            ..Config::default()
+        };
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let page: String = export_man_page(&root, &repository, &config, &mut diagnostics);
+
+        assert!(diagnostics.is_empty());
+        assert!(page.starts_with("'\\\" t\n.TH \"BILLING\\-CLI\" \"1\" \""));
+        assert!(page.contains("\"Billing ebb34e7\" \"Billing\"\n.SH NAME\nbilling\\-cli \\- Bill the customers\n"));
+        assert!(page.contains(".SH DESCRIPTION\n.PP\nRuns the \\f[I]monthly\\f[R] billing.\n"));
+        assert!(page.contains(".SH OPTIONS\n.IP \\(bu 2\n\\f[CR]\\-\\-dry\\-run\\f[R]: Prints the bills.\n.IP \\(bu 2\n"));
+        assert!(page.contains(".PP\n.RS 4\n.EX\n\\&.hidden\nbilling \\-\\-dry\\-run\n.EE\n.RE\n"));
+        assert!(page.contains(".TS\nallbox;\nlb cb\nl c.\nCode\tMeaning\n0\tBilled\n.TE\n"));
+        assert!(page.contains(".SH EXIT CODES\n.PP\n\\f[B]Retries\\f[R]\n.PP\nSee the runbook <https://runbook.acme.com>.\n"));
+
+        let config: Config = Config {
+            man_path: Some("Tools > Missing".to_string()),
+            ..Config::default()
+        };
+        assert!(export_man_page(&root, &repository, &config, &mut diagnostics).is_empty());
+        assert_eq!(diagnostics.len(), 1);
+    }
+}
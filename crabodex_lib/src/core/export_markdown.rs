@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use pulldown_cmark::{Event, LinkType, Parser, Tag, TagEnd};
+
+use crate::core::collect_headings::{collect_headings, slugify};
+use crate::core::diagnostic::Diagnostic;
+use crate::core::markdown_options::markdown_options;
+use crate::core::repository::Repository;
+use crate::core::resolve_includes::{follow_transclusion_marker, paired_transclusion_markers, transclusion_marker};
+use crate::core::resolve_relative_path::resolve_relative_path;
+use crate::DocNode;
+
+/// Exports the documentation as a single markdown document.
+///
+/// The sections come in the order of the navigation, each one under a heading regenerated from
+/// its depth. The headings of the contents are shifted below the one of their section, and keep
+/// the anchors of the HTML document as `{#id}` attributes. The links to the other markdown files
+/// point at the anchors of their sections, and the other relative links are made relative to the
+/// root directory.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `repository` - The repository the documentation comes from.
+/// * `diagnostics` - The list the links to files outside of the documentation are added to.
+///
+/// # Returns
+/// The documentation as a markdown string.
+///
+#[must_use]
+pub fn export_markdown(root: &DocNode, repository: &Repository, diagnostics: &mut Vec<Diagnostic>) -> String {
+    let mut files: HashMap<String, &DocNode> = HashMap::new();
+    collect_files(root, &mut files);
+
+    let mut markdown: String = String::default();
+    if !repository.name.is_empty() {
+        markdown.push_str(&format!("**{}**\n\n", repository.name));
+    }
+    if !repository.description.is_empty() {
+        markdown.push_str(&format!("{}\n\n", repository.description));
+    }
+    push_sections(root, 1, &files, &mut markdown, diagnostics);
+
+    format!("{}\n", markdown.trim_end())
+}
+
+/// Maps the markdown files to the node of their content, recursively.
+fn collect_files<'a>(node: &'a DocNode, files: &mut HashMap<String, &'a DocNode>) {
+    for child in node.children.values() {
+        if let Some(file_path) = &child.file_path {
+            files.insert(file_path.clone(), child);
+        }
+        collect_files(child, files);
+    }
+}
+
+/// Adds the children of a node to the document, with their own children, recursively.
+fn push_sections(
+    node: &DocNode,
+    depth: usize,
+    files: &HashMap<String, &DocNode>,
+    markdown: &mut String,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for child in node.sorted_children() {
+        markdown.push_str(&format!("{} {} {{#{}}}\n\n", "#".repeat(depth.min(6)), child.title, child.id()));
+
+        if let Some(content) = &child.content {
+            let content: String = rewrite_links(child, content, files, diagnostics);
+            let content: String = shift_headings(child, &content, depth);
+            if !content.trim().is_empty() {
+                markdown.push_str(content.trim());
+                markdown.push_str("\n\n");
+            }
+        }
+
+        push_sections(child, depth + 1, files, markdown, diagnostics);
+    }
+}
+
+/// Rewrites the relative links and images of the content of a node.
+///
+/// The links are relative to the file they are written in, the node's or an included one.
+fn rewrite_links(
+    node: &DocNode,
+    content: &str,
+    files: &HashMap<String, &DocNode>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    let Some(file_path) = node.file_path.as_deref() else {
+        return content.to_string();
+    };
+
+    let parser: Parser = Parser::new_ext(content, markdown_options());
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let definitions: Vec<(String, Range<usize>)> = parser
+        .reference_definitions()
+        .iter()
+        .map(|(_, definition)| (definition.dest.to_string(), definition.span.clone()))
+        .collect();
+    let (events, ranges): (Vec<Event>, Vec<Range<usize>>) = parser.into_offset_iter().unzip();
+    let transclusion_markers: HashSet<usize> = paired_transclusion_markers(&events);
+
+    // The offsets where the content starts coming from another file, with that file
+    let mut file_changes: Vec<(usize, String)> = vec![(0, file_path.to_string())];
+    let mut file_paths: Vec<String> = vec![file_path.to_string()];
+    for (index, (event, range)) in events.into_iter().zip(ranges).enumerate() {
+        match event {
+            Event::Html(raw) if transclusion_markers.contains(&index) => {
+                if let Some(marker) = transclusion_marker(&raw) {
+                    follow_transclusion_marker(&mut file_paths, &marker);
+                    file_changes.push((range.end, file_paths.last().cloned().unwrap_or_default()));
+                }
+            }
+            Event::Start(
+                Tag::Link { link_type: LinkType::Inline, dest_url, .. } | Tag::Image { link_type: LinkType::Inline, dest_url, .. },
+            ) => {
+                let link_file_path: &str = file_at(&file_changes, range.start);
+                let destination: Option<String> = rewrite_link(&dest_url, node, link_file_path, files, diagnostics);
+                push_link_edit(content, range, &dest_url, destination, &mut edits);
+            }
+            _ => {}
+        }
+    }
+
+    // The destinations of the reference links are written in their definitions
+    for (destination, span) in definitions {
+        let definition_file_path: &str = file_at(&file_changes, span.start);
+        let new_destination: Option<String> = rewrite_link(&destination, node, definition_file_path, files, diagnostics);
+        push_link_edit(content, span, &destination, new_destination, &mut edits);
+    }
+
+    apply_edits(content, edits)
+}
+
+/// Finds the file a part of a content comes from, given the offsets where the content changes files.
+fn file_at(file_changes: &[(usize, String)], offset: usize) -> &str {
+    file_changes
+        .iter()
+        .rev()
+        .find(|(start, _)| *start <= offset)
+        .map_or("", |(_, file_path)| file_path.as_str())
+}
+
+/// Adds the replacement of the destination of a link, found in its source, to the edits of a content.
+fn push_link_edit(
+    content: &str,
+    range: Range<usize>,
+    destination: &str,
+    new_destination: Option<String>,
+    edits: &mut Vec<(Range<usize>, String)>,
+) {
+    let Some(new_destination) = new_destination else {
+        return;
+    };
+    // The destination comes last in the source of the link, after its text
+    if let Some(offset) = content[range.clone()].rfind(destination) {
+        let start: usize = range.start + offset;
+        edits.push((start..start + destination.len(), new_destination));
+    }
+}
+
+/// Rewrites the destination of a link written in a markdown file, `None` to keep it as written.
+///
+/// The links to the markdown files of the documentation, or to the headings of their content,
+/// point at the anchors of their sections.
+fn rewrite_link(
+    destination: &str,
+    node: &DocNode,
+    file_path: &str,
+    files: &HashMap<String, &DocNode>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let (path, fragment): (&str, Option<&str>) = match destination.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (destination, None),
+    };
+    // The absolute paths and the URLs with a scheme are not relative to the file
+    if path.starts_with('/') || path.contains(':') {
+        return None;
+    }
+    if path.is_empty() {
+        return heading_anchor(node, fragment?).map(|anchor| format!("#{anchor}"));
+    }
+
+    let resolved: String = resolve_relative_path(file_path, path)?.to_string_lossy().to_string();
+    if !resolved.to_lowercase().ends_with(".md") {
+        return Some(match fragment {
+            Some(fragment) => format!("{resolved}#{fragment}"),
+            None => resolved,
+        });
+    }
+
+    let Some(target) = files.get(&resolved) else {
+        diagnostics.push(Diagnostic::new(
+            Some(file_path),
+            &format!("link to `{destination}` is outside of the documentation, it is kept as written"),
+        ));
+        return None;
+    };
+    let anchor: String = fragment
+        .and_then(|fragment| heading_anchor(target, fragment))
+        .unwrap_or_else(|| target.id());
+    Some(format!("#{anchor}"))
+}
+
+/// Finds the anchor of the heading of a node a fragment points at, by anchor or by title.
+fn heading_anchor(node: &DocNode, fragment: &str) -> Option<String> {
+    collect_headings(node)
+        .into_iter()
+        .find(|heading| heading.id == fragment || slugify(&heading.title) == fragment)
+        .map(|heading| heading.id)
+}
+
+/// Shifts the headings of the content of a node below the heading of the node.
+///
+/// The headings keep the anchors of the HTML document, and the markers of the included files are removed.
+fn shift_headings(node: &DocNode, content: &str, depth: usize) -> String {
+    // The headings come in the same order as the events, with their anchors
+    let mut headings = collect_headings(node).into_iter();
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    // The level of the current heading, its source and the source of its text
+    let mut current: Option<(usize, Range<usize>, Option<Range<usize>>)> = None;
+
+    for (event, range) in Parser::new_ext(content, markdown_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => current = Some((level as usize, range, None)),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, heading_range, text_range)) = current.take() {
+                    let id: String = headings.next().map(|heading| heading.id).unwrap_or_default();
+                    let title: &str = text_range.map(|text_range| content[text_range].trim()).unwrap_or_default();
+                    let newline: &str = if content[heading_range.clone()].ends_with('\n') { "\n" } else { "" };
+                    edits.push((heading_range, format!("{} {title} {{#{id}}}{newline}", "#".repeat((level + depth).min(6)))));
+                }
+            }
+            Event::Html(raw) if transclusion_marker(&raw).is_some() => edits.push((range, String::default())),
+            _ => {
+                if let Some((_, _, text_range)) = &mut current {
+                    *text_range = Some(match text_range {
+                        Some(text_range) => text_range.start..range.end,
+                        None => range,
+                    });
+                }
+            }
+        }
+    }
+
+    apply_edits(content, edits)
+}
+
+/// Replaces ranges of a content, skipping the ones overlapping a previous one.
+fn apply_edits(content: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut edited: String = String::with_capacity(content.len());
+    let mut cursor: usize = 0;
+
+    for (range, replacement) in edits {
+        if range.start < cursor {
+            continue;
+        }
+        edited.push_str(&content[cursor..range.start]);
+        edited.push_str(&replacement);
+        cursor = range.end;
+    }
+
+    edited.push_str(&content[cursor..]);
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_markdown() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        pricing.position = Some(1);
+        pricing.file_path = Some("pricing/index.md".to_string());
+        pricing.content = Some(
+            "# Discounts\n\nSee the [refunds](../refunds.md#delays), the [chart](chart.png) and the [site](https://acme.com).\n\n<!-- crabodex:transclusion-start rules.md -->\n\nRules, see the [delays](refunds.md#delays) and the [terms].\n\n[terms]: refunds.md\n\n<!-- crabodex:transclusion-end -->"
+                .to_string(),
+        );
+        let mut seasonal: DocNode = DocNode::new("Seasonal", "Pricing > Seasonal");
+        seasonal.file_path = Some("pricing/seasonal.md".to_string());
+        seasonal.content = Some("Back to the [discounts](index.md#discounts), or [elsewhere](other.md).".to_string());
+        pricing.children.insert("Seasonal".to_string(), seasonal);
+        let mut refunds: DocNode = DocNode::new("Refunds", "Refunds");
+        refunds.position = Some(2);
+        refunds.file_path = Some("refunds.md".to_string());
+        refunds.content = Some("## Delays\n\nTwo weeks.".to_string());
+        root.children.insert("Pricing".to_string(), pricing);
+        root.children.insert("Refunds".to_string(), refunds);
+
+        let repository: Repository = Repository { name: "Billing".to_string(), ..Repository::default() };
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let markdown: String = export_markdown(&root, &repository, &mut diagnostics);
+
+        assert!(markdown.starts_with("**Billing**\n\n# Pricing {#pricing}\n\n## Discounts {#pricing--discounts}\n"));
+        assert!(markdown.contains("See the [refunds](#refunds--delays), the [chart](pricing/chart.png) and the [site](https://acme.com)."));
+        assert!(markdown.contains("## Seasonal {#pricing->-seasonal}\n\nBack to the [discounts](#pricing--discounts), or [elsewhere](other.md)."));
+        assert!(markdown.contains("# Refunds {#refunds}\n\n### Delays {#refunds--delays}\n"));
+        assert!(markdown.contains("Rules, see the [delays](#refunds--delays) and the [terms].\n\n[terms]: #refunds"));
+        assert!(!markdown.contains("crabodex:transclusion"));
+        assert_eq!(diagnostics.len(), 1);
+    }
+}
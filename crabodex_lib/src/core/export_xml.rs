@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use crate::core::build_page_body::render_content;
+use crate::core::build_source_link::build_source_link;
+use crate::core::config::Config;
+use crate::core::constants::{XML_NAMESPACE, XML_SCHEMA_URL, XML_SCHEMA_VERSION};
+use crate::core::diagnostic::Diagnostic;
+use crate::core::escape_html::escape_html;
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// Exports the documentation tree as XML, following the published XML Schema.
+///
+/// The sections come in the order of the navigation, like in the JSON export. Each one has a
+/// reference to its source file with its lines, and the repository it comes from when it is not
+/// the one of its parent.
+///
+/// # Arguments
+/// * `root` - The root of the document structure.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `repository` - The repository the documentation comes from.
+/// * `config` - The configuration of the documentation generation.
+/// * `diagnostics` - The list the warnings raised while rendering the content are added to.
+///
+/// # Returns
+/// The documentation as an XML string.
+///
+#[must_use]
+pub fn export_xml(
+    root: &DocNode,
+    root_directory: &Path,
+    repository: &Repository,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    let mut xml: String = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<documentation xmlns=\"{XML_NAMESPACE}\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"{XML_NAMESPACE} {XML_SCHEMA_URL}\" schema-version=\"{XML_SCHEMA_VERSION}\">\n"
+    ));
+    push_repository(repository, 1, &mut xml);
+
+    let repository_link: (&str, &str) = (&repository.url, &repository.source_ref);
+    for child in root.sorted_children() {
+        push_section(child, &[], root_directory, repository_link, config, diagnostics, &mut xml);
+    }
+
+    xml.push_str("</documentation>\n");
+    xml
+}
+
+/// Adds a repository to the document, at an indentation level.
+fn push_repository(repository: &Repository, indent: usize, xml: &mut String) {
+    let padding: String = "  ".repeat(indent);
+    xml.push_str(&format!("{padding}<repository>\n"));
+    push_element("name", &repository.name, indent + 1, xml);
+    push_element("description", &repository.description, indent + 1, xml);
+    push_element("url", &repository.url, indent + 1, xml);
+    push_element("commit-hash", &repository.commit_hash, indent + 1, xml);
+    push_element("source-ref", &repository.source_ref, indent + 1, xml);
+    push_element("forge", &repository.forge.to_string(), indent + 1, xml);
+    xml.push_str(&format!("{padding}</repository>\n"));
+}
+
+/// Adds a node and its children to the document recursively.
+fn push_section(
+    node: &DocNode,
+    parent_path: &[&str],
+    root_directory: &Path,
+    repository: (&str, &str),
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+    xml: &mut String,
+) {
+    let mut path: Vec<&str> = parent_path.to_vec();
+    path.push(node.path.rsplit(" > ").next().unwrap_or_default());
+    let depth: usize = path.len();
+    let padding: String = "  ".repeat(depth);
+    // The repository of the node is only exported where it differs from the one of its parent
+    let node_repository: Option<&Repository> = node
+        .repository
+        .as_ref()
+        .filter(|node_repository| (node_repository.url.as_str(), node_repository.source_ref.as_str()) != repository);
+    let repository: (&str, &str) = match node_repository {
+        Some(node_repository) => (&node_repository.url, &node_repository.source_ref),
+        None => repository,
+    };
+    // The links to the imported sections follow the format of their forge
+    let link_config: Option<Config> = node_repository.map(|node_repository| node_repository.link_config(config));
+    let config: &Config = link_config.as_ref().unwrap_or(config);
+
+    let position: String = node.position.map(|position| format!(" position=\"{position}\"")).unwrap_or_default();
+    xml.push_str(&format!("{padding}<section slug=\"{}\" depth=\"{depth}\"{position}>\n", escape_xml(&node.id())));
+    push_element("title", &node.title, depth + 1, xml);
+
+    xml.push_str(&format!("{padding}  <path>\n"));
+    for segment in &path {
+        push_element("segment", segment, depth + 2, xml);
+    }
+    xml.push_str(&format!("{padding}  </path>\n"));
+
+    if let Some(file_path) = &node.file_path {
+        let mut source: String = format!("{padding}  <source file=\"{}\"", escape_xml(file_path));
+        if let Some(start_line) = node.start_line {
+            source.push_str(&format!(" start-line=\"{start_line}\""));
+        }
+        if let Some(end_line) = node.end_line {
+            source.push_str(&format!(" end-line=\"{end_line}\""));
+        }
+        // Without the URL of the repository or the ref to link to, there is nothing to link to
+        let lines: Option<(usize, usize)> = node.start_line.zip(node.end_line);
+        if let Some(url) = build_source_link(repository.0, repository.1, file_path, lines, config).filter(|_| !repository.0.is_empty()) {
+            source.push_str(&format!(" url=\"{}\"", escape_xml(&url)));
+        }
+        xml.push_str(&format!("{source}/>\n"));
+    }
+    if let Some(description) = &node.description {
+        push_element("description", description, depth + 1, xml);
+    }
+
+    if node.tags.is_empty() {
+        xml.push_str(&format!("{padding}  <tags/>\n"));
+    } else {
+        xml.push_str(&format!("{padding}  <tags>\n"));
+        for tag in &node.tags {
+            push_element("tag", tag, depth + 2, xml);
+        }
+        xml.push_str(&format!("{padding}  </tags>\n"));
+    }
+
+    if let Some(front_matter) = &node.front_matter {
+        push_element("front-matter", front_matter, depth + 1, xml);
+    }
+    if let Some(content) = &node.content {
+        push_element("markdown", content, depth + 1, xml);
+        let html: String = render_content(node, content, root_directory, depth, repository, config, diagnostics);
+        push_element("html", &html, depth + 1, xml);
+    }
+    if let Some(node_repository) = node_repository {
+        push_repository(node_repository, depth + 1, xml);
+    }
+
+    for child in node.sorted_children() {
+        push_section(child, &path, root_directory, repository, config, diagnostics, xml);
+    }
+    xml.push_str(&format!("{padding}</section>\n"));
+}
+
+/// Adds an element with some text to the document, at an indentation level.
+fn push_element(name: &str, text: &str, indent: usize, xml: &mut String) {
+    xml.push_str(&format!("{}<{name}>{}</{name}>\n", "  ".repeat(indent), escape_xml(text)));
+}
+
+/// Escapes a text for XML, without the control characters XML 1.0 does not allow.
+fn escape_xml(text: &str) -> String {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    escape_html(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_xml() {
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut pricing: DocNode = DocNode::new("Pricing", "Pricing");
+        let mut discounts: DocNode = DocNode::new("Discounts", "Pricing > Discounts");
+        discounts.position = Some(2);
+        discounts.file_path = Some("pricing/discounts.md".to_string());
+        discounts.start_line = Some(5);
+        discounts.end_line = Some(9);
+        discounts.tags = vec!["billing".to_string()];
+        discounts.description = Some("Loyalty & volume discounts".to_string());
+        discounts.content = Some("Up to *10%*.".to_string());
+        pricing.children.insert("Discounts".to_string(), discounts);
+        root.children.insert("Pricing".to_string(), pricing);
+
+        let repository: Repository = Repository {
+            name: "Billing".to_string(),
+            description: String::default(),
+            url: "https://github.com/acme/billing".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            source_ref: "ebb34e7".to_string(),
+            ..Repository::default()
+        };
+        let xml: String = export_xml(&root, Path::new("."), &repository, &Config::default(), &mut Vec::new());
+
+        assert!(xml.contains(&format!("xsi:schemaLocation=\"{XML_NAMESPACE} {XML_SCHEMA_URL}\" schema-version=\"1\">\n")));
+        assert!(xml.contains("  <repository>\n    <name>Billing</name>\n    <description></description>\n"));
+        assert!(xml.contains("    <commit-hash>ebb34e7</commit-hash>\n    <source-ref>ebb34e7</source-ref>\n    <forge>github</forge>\n"));
+        assert!(xml.contains("  <section slug=\"pricing\" depth=\"1\">\n    <title>Pricing</title>\n    <path>\n      <segment>Pricing</segment>\n    </path>\n    <tags/>\n"));
+        assert!(xml.contains("    <section slug=\"pricing-&gt;-discounts\" depth=\"2\" position=\"2\">\n"));
+        assert!(xml.contains(
+            "      <source file=\"pricing/discounts.md\" start-line=\"5\" end-line=\"9\" \
+             url=\"https://github.com/acme/billing/blob/ebb34e7/pricing/discounts.md?plain=1#L5-L9\"/>\n"
+        ));
+        assert!(xml.contains("      <description>Loyalty &amp; volume discounts</description>\n"));
+        assert!(xml.contains("      <tags>\n        <tag>billing</tag>\n      </tags>\n"));
+        assert!(xml.contains("      <markdown>Up to *10%*.</markdown>\n"));
+        assert!(xml.contains("&lt;em&gt;10%&lt;/em&gt;"));
+        assert!(xml.ends_with("  </section>\n</documentation>\n"));
+    }
+}
@@ -61,7 +61,7 @@ mod tests {
 
         let markdown_files: Vec<PathBuf> = find_markdown_files(test_dir, &[]);
 
-        assert_eq!(markdown_files.len(), 8);
+        assert_eq!(markdown_files.len(), 10);
         assert!(markdown_files
             .iter()
             .any(|p| p.file_name().unwrap() == "file1.md"));
@@ -0,0 +1,206 @@
+use crate::core::constants::JSON_SCHEMA_VERSION;
+use crate::core::diagnostic::Diagnostic;
+use crate::core::export_json::{JsonDocument, JsonNode};
+use crate::core::parse_front_matter::normalize_description;
+use crate::core::repository::Repository;
+use crate::DocNode;
+
+/// Parses a JSON export of a documentation tree.
+///
+/// # Arguments
+/// * `json` - The JSON export.
+///
+/// # Returns
+/// The exported documentation.
+///
+/// # Errors
+/// If the JSON does not follow the schema, or follows another version of it.
+///
+pub fn import_json(json: &str) -> Result<JsonDocument, String> {
+    let document: JsonDocument = serde_json::from_str(json).map_err(|error| format!("invalid JSON export: {error}"))?;
+    if document.schema_version != JSON_SCHEMA_VERSION {
+        return Err(format!(
+            "unsupported schema version {}, expected {JSON_SCHEMA_VERSION}",
+            document.schema_version
+        ));
+    }
+    Ok(document)
+}
+
+/// Mounts the sections of an exported documentation under a path of a documentation tree.
+///
+/// The sections keep the repository they come from, so they link to their own sources.
+/// A section already in the tree is merged with the mounted one, and its content is replaced.
+///
+/// # Arguments
+/// * `root` - The root of the document structure the sections are mounted in.
+/// * `document` - The exported documentation.
+/// * `mount_path` - The path the sections are mounted under, empty to mount them at the top level.
+/// * `diagnostics` - The list the warnings raised while mounting are added to.
+///
+pub fn mount_document(root: &mut DocNode, document: JsonDocument, mount_path: &[String], diagnostics: &mut Vec<Diagnostic>) {
+    let mut current: &mut DocNode = root;
+    let mut current_path: String = String::default();
+
+    for section in mount_path {
+        if !current_path.is_empty() { current_path.push_str(" > ") }
+        current_path.push_str(section);
+
+        current = current
+            .children
+            .entry(section.clone())
+            .or_insert_with(|| DocNode::new(section, &current_path));
+    }
+
+    for section in document.sections {
+        let node: DocNode = doc_node(section, &current_path, mount_path.len(), &document.repository);
+        insert_node(current, node, diagnostics);
+    }
+}
+
+/// Converts an exported section and its children to a node mounted under a path, recursively.
+///
+/// Every node keeps the repository it comes from: the one of the section, or the inherited one.
+fn doc_node(section: JsonNode, parent_path: &str, parent_depth: usize, repository: &Repository) -> DocNode {
+    let key: String = section.path.last().cloned().unwrap_or_else(|| section.title.clone());
+    let path: String = if parent_path.is_empty() { key } else { format!("{parent_path} > {key}") };
+    let repository: Repository = section.repository.unwrap_or_else(|| repository.clone());
+
+    let mut node: DocNode = DocNode::new(&section.title, &path);
+    node.depth = parent_depth + 1;
+    node.position = section.position;
+    node.file_path = section.file_path;
+    node.start_line = section.start_line;
+    node.end_line = section.end_line;
+    node.tags = section.tags;
+    node.description = section
+        .front_matter
+        .as_ref()
+        .and_then(|front_matter| front_matter["description"].as_str())
+        .and_then(normalize_description);
+    node.front_matter = section
+        .front_matter
+        .and_then(|front_matter| serde_yaml::to_string(&front_matter).ok())
+        .map(|front_matter| front_matter.trim().to_string());
+    node.content = section.markdown;
+
+    for child in section.children {
+        let child: DocNode = doc_node(child, &path, parent_depth + 1, &repository);
+        // The keys of the children are unique in the export, they can not collide
+        node.children.insert(node_key(&child), child);
+    }
+
+    node.repository = Some(repository);
+    node
+}
+
+/// Adds a node to the children of a parent, merging it with the child with the same key.
+fn insert_node(parent: &mut DocNode, node: DocNode, diagnostics: &mut Vec<Diagnostic>) {
+    let key: String = node_key(&node);
+    let Some(existing) = parent.children.get_mut(&key) else {
+        parent.children.insert(key, node);
+        return;
+    };
+
+    if node.content.is_some() {
+        if existing.content.is_some() {
+            diagnostics.push(Diagnostic::new(
+                node.file_path.as_deref(),
+                &format!("section `{}` is defined more than once, the last one is kept", node.path),
+            ));
+        }
+        existing.title = node.title;
+        existing.depth = node.depth;
+        existing.position = node.position;
+        existing.file_path = node.file_path;
+        existing.start_line = node.start_line;
+        existing.end_line = node.end_line;
+        existing.tags = node.tags;
+        existing.description = node.description;
+        existing.front_matter = node.front_matter;
+        existing.content = node.content;
+        existing.repository = node.repository;
+    }
+    for child in node.children.into_values() {
+        insert_node(existing, child, diagnostics);
+    }
+}
+
+/// The key of a node among the children of its parent: the last element of its path.
+fn node_key(node: &DocNode) -> String {
+    node.path.rsplit(" > ").next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::core::build_doc_structure::build_doc_structure;
+    use crate::core::config::{Config, Forge};
+    use crate::core::export_json::export_json;
+    use crate::find_markdown_files;
+
+    use super::*;
+
+    #[test]
+    fn test_mount_document() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+
+        let markdown_files: Vec<PathBuf> = find_markdown_files(test_dir.clone(), &["ignored_test_files".to_string()]);
+        let doc_structure: DocNode = build_doc_structure(&markdown_files, test_dir.as_path());
+        let repository: Repository = Repository {
+            name: "Billing".to_string(),
+            url: "https://github.com/acme/billing".to_string(),
+            commit_hash: "ebb34e7".to_string(),
+            source_ref: "ebb34e7".to_string(),
+            forge: Forge::GitLab,
+            ..Repository::default()
+        };
+        let json: String = export_json(&doc_structure, &test_dir, repository.clone(), &Config::default(), &mut Vec::new());
+
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        mount_document(&mut root, import_json(&json).unwrap(), &["Services".to_string(), "Billing".to_string()], &mut diagnostics);
+
+        let billing: &DocNode = &root.children["Services"].children["Billing"];
+        let getting_started: &DocNode = &billing.children["Getting Started"];
+        // The repository comes with its forge
+        assert_eq!(getting_started.repository, Some(repository));
+        assert_eq!(getting_started.children["Configuration"].path, "Services > Billing > Getting Started > Configuration");
+        assert_eq!(getting_started.children["Configuration"].file_path.as_deref(), Some("sub_dir_2/file2.md"));
+        assert!(diagnostics.is_empty());
+
+        mount_document(&mut root, import_json(&json).unwrap(), &["Services".to_string(), "Billing".to_string()], &mut diagnostics);
+        assert!(!diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_mount_document_description() {
+        let section = |title: &str, description: &str| {
+            format!(
+                r#"{{"title": "{title}", "path": ["{title}"], "slug": "", "position": null, "depth": 1, "file_path": null, "start_line": null, "end_line": null, "tags": [], "front_matter": {{"description": "{description}"}}, "markdown": "", "html": "", "children": []}}"#
+            )
+        };
+        let json: String = format!(
+            r#"{{"$schema": "", "schema_version": 1, "repository": {{"name": "", "description": "", "url": "", "commit_hash": "", "source_ref": ""}}, "sections": [{}, {}]}}"#,
+            section("Pricing", "How the prices\\n  are computed. "),
+            section("Refunds", " \\n ")
+        );
+
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        mount_document(&mut root, import_json(&json).unwrap(), &[], &mut Vec::new());
+
+        // The description is on one line, like the one read from the markdown files
+        assert_eq!(root.children["Pricing"].description.as_deref(), Some("How the prices are computed."));
+        assert_eq!(root.children["Refunds"].description, None);
+    }
+
+    #[test]
+    fn test_import_json_schema_version() {
+        let json: &str = r#"{"$schema": "", "schema_version": 2, "repository": {"name": "", "description": "", "url": "", "commit_hash": "", "source_ref": ""}, "sections": []}"#;
+        assert_eq!(import_json(json).unwrap_err(), "unsupported schema version 2, expected 1");
+        assert!(import_json("{}").is_err());
+    }
+}
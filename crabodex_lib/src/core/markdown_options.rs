@@ -0,0 +1,22 @@
+use pulldown_cmark::Options;
+
+/// The markdown extensions enabled when parsing the content of the nodes.
+///
+/// # Returns
+/// The options to give to the pulldown-cmark parser.
+///
+#[must_use]
+pub fn markdown_options() -> Options {
+    let mut options: Options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_DEFINITION_LIST);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_GFM);
+    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    options.insert(Options::ENABLE_MATH);
+    options.insert(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
+    options.insert(Options::ENABLE_SMART_PUNCTUATION);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options
+}
@@ -5,4 +5,36 @@ pub mod build_doc_structure;
 pub mod constants;
 pub mod build_navigation;
 pub mod build_page_body;
-pub mod create_html_document;
\ No newline at end of file
+pub mod create_html_document;
+pub mod config;
+pub mod diagnostic;
+pub mod sanitize_html;
+pub mod markdown_options;
+pub mod escape_html;
+pub mod collect_headings;
+pub mod build_breadcrumbs;
+pub mod check_depth;
+pub mod build_sibling_links;
+pub mod render_diagram;
+pub mod render_math;
+pub mod render_admonitions;
+pub mod resolve_includes;
+pub mod strip_front_matter;
+pub mod resolve_relative_path;
+pub mod resolve_image_url;
+pub mod build_source_link;
+pub mod detect_git_repository;
+pub mod build_search_index;
+pub mod export_json;
+pub mod repository;
+pub mod import_json;
+pub mod write_output_files;
+pub mod build_site;
+pub mod export_markdown;
+pub mod export_llms_text;
+pub mod build_zip_archive;
+pub mod export_epub;
+pub mod export_confluence;
+pub mod export_latex;
+pub mod export_man_page;
+pub mod export_xml;
@@ -30,16 +30,7 @@ use crate::core::constants::DEFAULT_FRONT_MATTER_PREFIX;
 /// 
 #[must_use]
 pub fn parse_front_matter(file_content: &str) -> Option<(String, Vec<String>, Option<usize>)> {
-    if !file_content.starts_with(DEFAULT_FRONT_MATTER_PREFIX) { return None; }
-    let content_after_prefix: &str = &file_content[DEFAULT_FRONT_MATTER_PREFIX.len()..];
-    let end_index: Option<usize> = content_after_prefix.find(DEFAULT_FRONT_MATTER_PREFIX);
-    end_index?;
-    let end_index: usize = end_index.unwrap();
-    let front_matter: &str = &content_after_prefix[..end_index];
-
-    let yaml: Vec<Yaml> = YamlLoader::load_from_str(front_matter).ok()?;
-    if yaml.is_empty() { return None; }
-    let doc: &Yaml = &yaml[0];
+    let doc: Yaml = front_matter_yaml(file_content)?;
 
     let path: Vec<String> = match doc["path"].as_vec() {
         Some(path_yaml) => {
@@ -61,6 +52,83 @@ pub fn parse_front_matter(file_content: &str) -> Option<(String, Vec<String>, Op
     Some((title, path, position))
 }
 
+/// Parses the tags of a markdown file from its Front Matter.
+///
+/// The "tags" field can be a list of strings, or a string of comma-separated tags.
+///
+/// # Arguments
+/// * `file_content` - The content of the markdown file.
+///
+/// # Returns
+/// The tags of the document, empty if there are none.
+///
+#[must_use]
+pub fn parse_front_matter_tags(file_content: &str) -> Vec<String> {
+    let Some(doc) = front_matter_yaml(file_content) else {
+        return Vec::new();
+    };
+
+    let tags: Vec<String> = match &doc["tags"] {
+        Yaml::Array(tags) => tags.iter().filter_map(|tag| tag.as_str().map(ToString::to_string)).collect(),
+        Yaml::String(tags) => tags.split(',').map(ToString::to_string).collect(),
+        _ => Vec::new(),
+    };
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Parses the description of a markdown file from its Front Matter.
+///
+/// The "description" field is a one-line summary of the document, its whitespace is collapsed.
+///
+/// # Arguments
+/// * `file_content` - The content of the markdown file.
+///
+/// # Returns
+/// The description of the document, or `None` if it has none.
+///
+#[must_use]
+pub fn parse_front_matter_description(file_content: &str) -> Option<String> {
+    let doc: Yaml = front_matter_yaml(file_content)?;
+    normalize_description(doc["description"].as_str()?)
+}
+
+/// Collapses the whitespace of a description, so it fits on one line.
+///
+/// # Arguments
+/// * `description` - The description, as written in the Front Matter.
+///
+/// # Returns
+/// The description on one line, or `None` if it is blank.
+///
+#[must_use]
+pub fn normalize_description(description: &str) -> Option<String> {
+    let description: String = description.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (!description.is_empty()).then_some(description)
+}
+
+/// Finds the YAML source of the Front Matter of a markdown file.
+///
+/// # Arguments
+/// * `file_content` - The content of the markdown file.
+///
+/// # Returns
+/// The source between the Front Matter delimiters, or `None` if there is no Front Matter.
+///
+#[must_use]
+pub fn front_matter_source(file_content: &str) -> Option<&str> {
+    let content_after_prefix: &str = file_content.strip_prefix(DEFAULT_FRONT_MATTER_PREFIX)?;
+    let end_index: usize = content_after_prefix.find(DEFAULT_FRONT_MATTER_PREFIX)?;
+    Some(&content_after_prefix[..end_index])
+}
+
+/// Loads the Front Matter of a markdown file as a YAML document.
+fn front_matter_yaml(file_content: &str) -> Option<Yaml> {
+    YamlLoader::load_from_str(front_matter_source(file_content)?).ok()?.into_iter().next()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +168,22 @@ This is the content.";
         assert_eq!(path, vec!["Test document"]);
         assert_eq!(position, Some(1));
     }
+
+    #[test]
+    fn test_parse_front_matter_tags() {
+        let list: &str = "---\npath:\n  - Pricing\ntags:\n  - billing\n  - discounts\n---\n";
+        let string: &str = "---\npath:\n  - Pricing\ntags: billing, discounts\n---\n";
+
+        assert_eq!(parse_front_matter_tags(list), vec!["billing", "discounts"]);
+        assert_eq!(parse_front_matter_tags(string), vec!["billing", "discounts"]);
+        assert!(parse_front_matter_tags("No front matter").is_empty());
+    }
+
+    #[test]
+    fn test_parse_front_matter_description() {
+        let file_content: &str = "---\npath:\n  - Pricing\ndescription: >\n  How the prices\n  are computed.\n---\n";
+
+        assert_eq!(parse_front_matter_description(file_content).as_deref(), Some("How the prices are computed."));
+        assert_eq!(parse_front_matter_description("---\npath:\n  - Pricing\n---\n"), None);
+    }
 }
@@ -0,0 +1,273 @@
+use pulldown_cmark::{BlockQuoteKind, CowStr, Event, Tag, TagEnd};
+
+/// The kinds of admonitions, with their title.
+const ADMONITION_KINDS: &[(&str, &str)] = &[
+    ("note", "Note"),
+    ("tip", "Tip"),
+    ("important", "Important"),
+    ("warning", "Warning"),
+    ("caution", "Caution"),
+    ("rule", "Rule"),
+    ("deprecated", "Deprecated"),
+];
+
+/// Turns the `:::kind` fenced containers of a markdown content into
+/// GitHub-style `> [!KIND]` blockquotes, so both syntaxes are rendered the same way.
+///
+/// A container starts with a `:::kind` line and ends with a `:::` line.
+/// Containers can be nested, and the `:::` containers of unknown kinds are kept as written
+/// with their closing line. The lines in fenced code blocks are left untouched, a fence
+/// being closed by a line of at least as many of its characters, and the lines outside
+/// of the containers are kept as they are.
+///
+/// # Arguments
+/// * `content` - The markdown content.
+///
+/// # Returns
+/// The markdown content with the containers turned into blockquotes.
+///
+#[must_use]
+pub fn expand_admonition_containers(content: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    // Whether each of the open containers is an admonition, the other ones are kept as written
+    let mut containers: Vec<bool> = Vec::new();
+    let mut depth: usize = 0;
+    let mut code_fence: Option<(char, usize)> = None;
+
+    for line in content.lines() {
+        let trimmed: &str = line.trim_start();
+
+        if let Some((fence_char, fence_length)) = code_fence {
+            if code_fence_run(trimmed).is_some_and(|(character, length)| {
+                character == fence_char && length >= fence_length && trimmed[length..].trim().is_empty()
+            }) {
+                code_fence = None;
+            }
+        } else if let Some(fence) = code_fence_run(trimmed) {
+            code_fence = Some(fence);
+        } else if let Some(kind) = trimmed.strip_prefix(":::").map(str::trim) {
+            if kind.is_empty() {
+                // The closing line of an unknown container is kept with it
+                if containers.pop() == Some(true) {
+                    depth -= 1;
+                    // An empty line ends the blockquote, the next lines are not lazily added to it
+                    lines.push("> ".repeat(depth).trim_end().to_string());
+                    continue;
+                }
+            } else if ADMONITION_KINDS.iter().any(|(name, _)| name.eq_ignore_ascii_case(kind)) {
+                lines.push(format!("{}> [!{}]", "> ".repeat(depth), kind.to_uppercase()));
+                containers.push(true);
+                depth += 1;
+                continue;
+            } else {
+                containers.push(false);
+            }
+        }
+
+        if depth == 0 || !line.is_empty() {
+            lines.push(format!("{}{}", "> ".repeat(depth), line));
+        } else {
+            // The empty lines are kept in the blockquote, without a trailing space
+            lines.push("> ".repeat(depth).trim_end().to_string());
+        }
+    }
+
+    let mut expanded: String = lines.join("\n");
+    if content.ends_with('\n') {
+        expanded.push('\n');
+    }
+    expanded
+}
+
+/// Finds the fence of a code block at the start of a line: its character and its length.
+fn code_fence_run(line: &str) -> Option<(char, usize)> {
+    let character: char = line.chars().next().filter(|character| matches!(character, '`' | '~'))?;
+    let length: usize = line.len() - line.trim_start_matches(character).len();
+    (length >= 3).then_some((character, length))
+}
+
+/// Renders the admonitions of a stream of markdown events as styled asides.
+///
+/// The blockquotes starting with a `[!KIND]` marker are admonitions. pulldown-cmark
+/// recognises the GitHub kinds itself, the other ones are found from the marker text.
+///
+/// # Arguments
+/// * `events` - The markdown events.
+///
+/// # Returns
+/// The markdown events with the admonitions rendered as HTML.
+///
+#[must_use]
+pub fn render_admonitions(events: Vec<Event>) -> Vec<Event> {
+    replace_admonitions(
+        events,
+        |kind, title| format!("<aside class=\"admonition admonition-{kind}\" role=\"note\"><p class=\"admonition-title\">{title}</p>"),
+        "</aside>",
+    )
+}
+
+/// Replaces the admonitions of a stream of markdown events with the markup of another format.
+///
+/// # Arguments
+/// * `events` - The markdown events.
+/// * `start` - Builds the markup opening an admonition, from its kind and its title.
+/// * `end` - The markup closing an admonition.
+///
+/// # Returns
+/// The markdown events with the admonitions replaced.
+///
+#[must_use]
+pub fn replace_admonitions<'a>(events: Vec<Event<'a>>, start: impl Fn(&str, &str) -> String, end: &'static str) -> Vec<Event<'a>> {
+    let mut rendered: Vec<Event> = Vec::with_capacity(events.len());
+    // Whether each of the open blockquotes is an admonition
+    let mut blockquotes: Vec<bool> = Vec::new();
+    let mut index: usize = 0;
+
+    while index < events.len() {
+        match &events[index] {
+            Event::Start(Tag::BlockQuote(kind)) => {
+                let admonition: Option<(&str, Marker)> = match kind {
+                    Some(kind) => Some((gfm_kind_name(*kind), Marker::Parsed)),
+                    None => find_marker(&events[index + 1..]),
+                };
+                let Some((kind, marker)) = admonition else {
+                    blockquotes.push(false);
+                    rendered.push(events[index].clone());
+                    index += 1;
+                    continue;
+                };
+
+                let title: &str = ADMONITION_KINDS
+                    .iter()
+                    .find(|(name, _)| *name == kind)
+                    .map_or(kind, |(_, title)| title);
+                rendered.push(Event::Html(CowStr::Boxed(start(kind, title).into())));
+                blockquotes.push(true);
+                // The blockquote start, and the events of the marker for the custom kinds
+                index += match marker {
+                    Marker::Parsed => 1,
+                    Marker::OwnParagraph => 6,
+                    Marker::OwnLine => {
+                        rendered.push(Event::Start(Tag::Paragraph));
+                        6
+                    }
+                };
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                let is_admonition: bool = blockquotes.pop() == Some(true);
+                rendered.push(if is_admonition {
+                    Event::Html(CowStr::Borrowed(end))
+                } else {
+                    events[index].clone()
+                });
+                index += 1;
+            }
+            event => {
+                rendered.push(event.clone());
+                index += 1;
+            }
+        }
+    }
+
+    rendered
+}
+
+fn gfm_kind_name(kind: BlockQuoteKind) -> &'static str {
+    match kind {
+        BlockQuoteKind::Note => "note",
+        BlockQuoteKind::Tip => "tip",
+        BlockQuoteKind::Important => "important",
+        BlockQuoteKind::Warning => "warning",
+        BlockQuoteKind::Caution => "caution",
+    }
+}
+
+/// How the `[!KIND]` marker of an admonition appears in the markdown events.
+enum Marker {
+    /// pulldown-cmark parsed it into the blockquote kind.
+    Parsed,
+    /// It is the whole first paragraph of the blockquote.
+    OwnParagraph,
+    /// It is the first line of the first paragraph of the blockquote.
+    OwnLine,
+}
+
+/// Finds a `[!KIND]` marker at the start of a blockquote that pulldown-cmark does not know.
+///
+/// The marker comes as `[`, `!KIND` and `]` text events at the start of the first paragraph.
+/// It is followed by a soft break, or by the end of the paragraph if it is alone in it.
+fn find_marker(events: &[Event]) -> Option<(&'static str, Marker)> {
+    let [Event::Start(Tag::Paragraph), Event::Text(open), Event::Text(marker), Event::Text(close), next, ..] = events else {
+        return None;
+    };
+    if open.as_ref() != "[" || close.as_ref() != "]" {
+        return None;
+    }
+    let (kind, _) = ADMONITION_KINDS
+        .iter()
+        .find(|(name, _)| marker.strip_prefix('!').is_some_and(|marker| marker.eq_ignore_ascii_case(name)))?;
+
+    match next {
+        Event::SoftBreak => Some((kind, Marker::OwnLine)),
+        Event::End(TagEnd::Paragraph) => Some((kind, Marker::OwnParagraph)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pulldown_cmark::{html::push_html, Parser};
+
+    use super::*;
+    use crate::core::markdown_options::markdown_options;
+
+    #[test]
+    fn test_expand_admonition_containers() {
+        let content: &str = ":::rule\nDiscounts never stack.\n\n:::warning\nReally.\n:::\n```\n:::rule\n```\n:::\nAfter";
+
+        assert_eq!(
+            expand_admonition_containers(content),
+            "> [!RULE]\n> Discounts never stack.\n>\n> > [!WARNING]\n> > Really.\n>\n> ```\n> :::rule\n> ```\n\nAfter"
+        );
+    }
+
+    #[test]
+    fn test_expand_admonition_containers_keeps_whitespace() {
+        let content: &str = "Line  \nbreak\n\n    indented code  \n\n:::note\nHard  \nbreak\n```\n  let a = 1;  \n```\n:::\n";
+
+        assert_eq!(
+            expand_admonition_containers(content),
+            "Line  \nbreak\n\n    indented code  \n\n> [!NOTE]\n> Hard  \n> break\n> ```\n>   let a = 1;  \n> ```\n\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_admonition_containers_nesting() {
+        // The closing line of an unknown container does not close the admonition around it
+        let content: &str = ":::note\n:::details\nHidden.\n:::\nStill a note.\n:::\nAfter";
+        assert_eq!(
+            expand_admonition_containers(content),
+            "> [!NOTE]\n> :::details\n> Hidden.\n> :::\n> Still a note.\n\nAfter"
+        );
+
+        // A fence is only closed by a fence at least as long
+        let content: &str = ":::tip\n````md\n```\n:::\n```\n````\n:::";
+        assert_eq!(
+            expand_admonition_containers(content),
+            "> [!TIP]\n> ````md\n> ```\n> :::\n> ```\n> ````\n"
+        );
+    }
+
+    #[test]
+    fn test_render_admonitions() {
+        let content: String = expand_admonition_containers("> [!NOTE]\n> Hello\n\n:::deprecated\nOld *rule*\n:::\n\n> Quote");
+        let events: Vec<Event> = render_admonitions(Parser::new_ext(&content, markdown_options()).collect());
+        let mut html: String = String::default();
+        push_html(&mut html, events.into_iter());
+
+        assert_eq!(
+            html,
+            "<aside class=\"admonition admonition-note\" role=\"note\"><p class=\"admonition-title\">Note</p>\n<p>Hello</p>\n</aside><aside class=\"admonition admonition-deprecated\" role=\"note\"><p class=\"admonition-title\">Deprecated</p>\n<p>Old <em>rule</em></p>\n</aside>\n<blockquote>\n<p>Quote</p>\n</blockquote>\n"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+
+use crate::core::constants::MERMAID_DIAGRAM_START;
+use crate::core::escape_html::escape_html;
+
+/// Renders a mermaid diagram as a container for the mermaid renderer.
+///
+/// The diagram is drawn in the browser by the mermaid script. Without it,
+/// the source of the diagram is displayed as is.
+///
+/// # Arguments
+/// * `source` - The source of the diagram.
+///
+/// # Returns
+/// The diagram container as an HTML string.
+///
+#[must_use]
+pub fn render_mermaid(source: &str) -> String {
+    format!("{MERMAID_DIAGRAM_START}{}</pre>", escape_html(source))
+}
+
+/// Renders a Graphviz `dot` graph to an inline SVG at build time.
+///
+/// # Arguments
+/// * `source` - The source of the graph.
+///
+/// # Returns
+/// * `Ok(svg)` - The graph as an inline SVG wrapped in a `figure`.
+/// * `Err(message)` - If the graph can not be parsed or laid out.
+///
+/// # Errors
+/// If the source is not a valid `dot` graph, or uses features the layout engine does not support.
+///
+pub fn render_dot(source: &str) -> Result<String, String> {
+    let graph = DotParser::new(source).process()?;
+
+    // The layout engine panics on some graphs it does not support
+    let svg: String = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut builder: GraphBuilder = GraphBuilder::new();
+        builder.visit_graph(&graph);
+        let mut visual_graph = builder.get();
+        let mut writer: SVGWriter = SVGWriter::new();
+        visual_graph.do_it(false, false, false, &mut writer);
+        writer.finalize()
+    }))
+    .map_err(|_| "the graph could not be laid out".to_string())?;
+
+    let svg: &str = svg.find("<svg").map_or(svg.as_str(), |start| &svg[start..]);
+    Ok(format!("<figure class=\"diagram\">{svg}</figure>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_mermaid() {
+        assert_eq!(
+            render_mermaid("graph TD;\n    A-->B;\n"),
+            "<pre class=\"mermaid\">graph TD;\n    A--&gt;B;\n</pre>"
+        );
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let svg: String = render_dot("digraph { order -> invoice; }").unwrap();
+        assert!(svg.starts_with("<figure class=\"diagram\"><svg "));
+        assert!(svg.contains("invoice"));
+
+        assert!(render_dot("digraph { order -> ; }").is_err());
+    }
+}
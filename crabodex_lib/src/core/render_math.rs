@@ -0,0 +1,346 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::core::escape_html::escape_html;
+
+/// The commands rendered as identifiers, with the character they stand for.
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"), ("epsilon", "ϵ"),
+    ("varepsilon", "ε"), ("zeta", "ζ"), ("eta", "η"), ("theta", "θ"), ("iota", "ι"),
+    ("kappa", "κ"), ("lambda", "λ"), ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"), ("pi", "π"),
+    ("rho", "ρ"), ("sigma", "σ"), ("tau", "τ"), ("upsilon", "υ"), ("phi", "ϕ"),
+    ("varphi", "φ"), ("chi", "χ"), ("psi", "ψ"), ("omega", "ω"), ("Gamma", "Γ"),
+    ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"), ("Xi", "Ξ"), ("Pi", "Π"),
+    ("Sigma", "Σ"), ("Phi", "Φ"), ("Psi", "Ψ"), ("Omega", "Ω"), ("infty", "∞"),
+    ("ell", "ℓ"), ("emptyset", "∅"),
+];
+
+/// The commands rendered as operators, with the character they stand for.
+const OPERATORS: &[(&str, &str)] = &[
+    ("times", "×"), ("cdot", "⋅"), ("div", "÷"), ("pm", "±"), ("mp", "∓"), ("le", "≤"),
+    ("leq", "≤"), ("ge", "≥"), ("geq", "≥"), ("neq", "≠"), ("ne", "≠"), ("approx", "≈"),
+    ("equiv", "≡"), ("sim", "∼"), ("propto", "∝"), ("lt", "<"), ("gt", ">"),
+    ("to", "→"), ("rightarrow", "→"), ("leftarrow", "←"), ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"), ("leftrightarrow", "↔"), ("Leftrightarrow", "⇔"), ("in", "∈"),
+    ("notin", "∉"), ("subset", "⊂"), ("subseteq", "⊆"), ("cup", "∪"), ("cap", "∩"),
+    ("forall", "∀"), ("exists", "∃"), ("neg", "¬"), ("land", "∧"), ("lor", "∨"),
+    ("sum", "∑"), ("prod", "∏"), ("int", "∫"), ("partial", "∂"), ("nabla", "∇"),
+    ("ldots", "…"), ("cdots", "⋯"), ("lbrace", "{"), ("rbrace", "}"), ("{", "{"),
+    ("}", "}"), ("%", "%"), ("$", "$"), ("&", "&"), ("|", "‖"), ("lfloor", "⌊"),
+    ("rfloor", "⌋"), ("lceil", "⌈"), ("rceil", "⌉"), ("langle", "⟨"), ("rangle", "⟩"),
+];
+
+/// The commands rendered as the name of a function.
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "cos", "cosh", "det", "exp", "gcd", "lim", "ln", "log",
+    "max", "min", "mod", "sin", "sinh", "sup", "inf", "tan", "tanh",
+];
+
+/// The operators whose limits are rendered under and over them in display mode.
+const LARGE_OPERATORS: &[&str] = &["∑", "∏", "lim", "max", "min", "sup", "inf"];
+
+/// The commands adding horizontal space, with its width.
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.167em"), (":", "0.222em"), (">", "0.222em"), (";", "0.278em"), (" ", "0.25em"),
+    ("quad", "1em"), ("qquad", "2em"), ("!", "-0.167em"),
+];
+
+/// Renders a TeX formula to MathML.
+///
+/// Only a common subset of TeX is supported: numbers, identifiers, operators,
+/// greek letters, `^` and `_`, `\frac`, `\sqrt`, `\text`, `\left` and `\right`,
+/// function names and spaces. The TeX source is kept as an annotation.
+///
+/// # Arguments
+/// * `tex` - The TeX formula.
+/// * `display` - Whether the formula is displayed as a block, or inline.
+///
+/// # Returns
+/// * `Ok(mathml)` - The formula as a MathML element.
+/// * `Err(message)` - If the formula uses something that is not supported.
+///
+/// # Errors
+/// If the formula is not valid TeX or uses an unsupported command.
+///
+pub fn render_math(tex: &str, display: bool) -> Result<String, String> {
+    let mut parser: MathParser = MathParser {
+        chars: tex.chars().peekable(),
+        display,
+    };
+    let row: Vec<String> = parser.parse_row(Stop::End)?;
+
+    Ok(format!(
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"{}\"><semantics><mrow>{}</mrow><annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+        if display { "block" } else { "inline" },
+        row.concat(),
+        escape_html(tex.trim())
+    ))
+}
+
+/// What ends the row being parsed.
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Stop {
+    End,
+    Brace,
+    Bracket,
+    Right,
+}
+
+struct MathParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    display: bool,
+}
+
+impl MathParser<'_> {
+    /// Parses elements until the stop, wrapping them with their scripts.
+    fn parse_row(&mut self, stop: Stop) -> Result<Vec<String>, String> {
+        let mut row: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match (self.chars.peek().copied(), stop) {
+                (None, Stop::End) => return Ok(row),
+                (None, _) => return Err("missing closing delimiter".to_string()),
+                (Some('}'), Stop::Brace) | (Some(']'), Stop::Bracket) => {
+                    self.chars.next();
+                    return Ok(row);
+                }
+                (Some('}'), _) => return Err("unexpected `}`".to_string()),
+                (Some('\\'), Stop::Right) if self.peek_command() == "right" => return Ok(row),
+                (Some('^' | '_'), _) => {
+                    let base: String = row.pop().unwrap_or_else(|| "<mrow></mrow>".to_string());
+                    row.push(self.parse_scripts(base)?);
+                }
+                _ => {
+                    let atom: String = self.parse_atom()?;
+                    row.push(atom);
+                }
+            }
+        }
+    }
+
+    /// Parses the `^` and `_` following a base.
+    fn parse_scripts(&mut self, base: String) -> Result<String, String> {
+        let mut superscript: Option<String> = None;
+        let mut subscript: Option<String> = None;
+
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some('^') if superscript.is_none() => {
+                    self.chars.next();
+                    superscript = Some(self.parse_argument()?);
+                }
+                Some('_') if subscript.is_none() => {
+                    self.chars.next();
+                    subscript = Some(self.parse_argument()?);
+                }
+                Some('^' | '_') => return Err("double script".to_string()),
+                _ => break,
+            }
+        }
+
+        let is_large: bool = self.display && LARGE_OPERATORS.iter().any(|op| base.contains(&format!(">{op}<")));
+        let (both, under, over) = if is_large {
+            ("munderover", "munder", "mover")
+        } else {
+            ("msubsup", "msub", "msup")
+        };
+        Ok(match (subscript, superscript) {
+            (Some(sub), Some(sup)) => format!("<{both}>{base}{sub}{sup}</{both}>"),
+            (Some(sub), None) => format!("<{under}>{base}{sub}</{under}>"),
+            (None, Some(sup)) => format!("<{over}>{base}{sup}</{over}>"),
+            (None, None) => base,
+        })
+    }
+
+    /// Parses a single element, or a group between braces.
+    fn parse_argument(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => {
+                self.chars.next();
+                Ok(format!("<mrow>{}</mrow>", self.parse_row(Stop::Brace)?.concat()))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let digit: char = self.chars.next().unwrap_or_default();
+                Ok(format!("<mn>{digit}</mn>"))
+            }
+            Some(_) => self.parse_atom(),
+            None => Err("missing argument".to_string()),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<String, String> {
+        let Some(c) = self.chars.next() else {
+            return Err("missing argument".to_string());
+        };
+
+        match c {
+            '{' => Ok(format!("<mrow>{}</mrow>", self.parse_row(Stop::Brace)?.concat())),
+            '\\' => self.parse_command(),
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number: String = c.to_string();
+                while let Some(digit) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(digit);
+                }
+                Ok(format!("<mn>{number}</mn>"))
+            }
+            c if c.is_alphabetic() => Ok(format!("<mi>{c}</mi>")),
+            '-' => Ok("<mo>−</mo>".to_string()),
+            '\'' => Ok("<mo>′</mo>".to_string()),
+            '+' | '=' | '<' | '>' | '*' | '/' | '(' | ')' | '[' | ']' | '|' | ',' | ';' | ':' | '!' | '?' => {
+                Ok(format!("<mo>{}</mo>", escape_html(&c.to_string())))
+            }
+            '~' => Ok("<mspace width=\"0.25em\"></mspace>".to_string()),
+            c => Err(format!("unsupported character `{c}`")),
+        }
+    }
+
+    fn parse_command(&mut self) -> Result<String, String> {
+        let name: String = self.read_command_name();
+
+        if let Some((_, identifier)) = IDENTIFIERS.iter().find(|(command, _)| *command == name) {
+            return Ok(format!("<mi>{identifier}</mi>"));
+        }
+        if let Some((_, operator)) = OPERATORS.iter().find(|(command, _)| *command == name) {
+            return Ok(format!("<mo>{}</mo>", escape_html(operator)));
+        }
+        if let Some((_, width)) = SPACES.iter().find(|(command, _)| *command == name) {
+            return Ok(format!("<mspace width=\"{width}\"></mspace>"));
+        }
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Ok(format!("<mi>{name}</mi>"));
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator: String = self.parse_argument()?;
+                let denominator: String = self.parse_argument()?;
+                Ok(format!("<mfrac>{numerator}{denominator}</mfrac>"))
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.chars.next_if_eq(&'[').is_some() {
+                    let index: String = self.parse_row(Stop::Bracket)?.concat();
+                    let radicand: String = self.parse_argument()?;
+                    Ok(format!("<mroot>{radicand}<mrow>{index}</mrow></mroot>"))
+                } else {
+                    Ok(format!("<msqrt>{}</msqrt>", self.parse_argument()?))
+                }
+            }
+            "text" | "textrm" | "mbox" => Ok(format!("<mtext>{}</mtext>", escape_html(&self.read_braced_text()?))),
+            "mathrm" | "operatorname" => Ok(format!(
+                "<mi mathvariant=\"normal\">{}</mi>",
+                escape_html(&self.read_braced_text()?)
+            )),
+            "mathbf" => Ok(format!("<mi mathvariant=\"bold\">{}</mi>", escape_html(&self.read_braced_text()?))),
+            "left" => {
+                let open: String = self.read_delimiter()?;
+                let content: String = self.parse_row(Stop::Right)?.concat();
+                // The backslash of `\right`, then its name
+                self.chars.next();
+                self.read_command_name();
+                let close: String = self.read_delimiter()?;
+                Ok(format!(
+                    "<mrow><mo fence=\"true\">{open}</mo>{content}<mo fence=\"true\">{close}</mo></mrow>"
+                ))
+            }
+            "right" => Err("`\\right` without `\\left`".to_string()),
+            // The line breaks need a table, which is not supported
+            "\\" => Err("unsupported line break `\\\\`".to_string()),
+            _ => Err(format!("unsupported command `\\{name}`")),
+        }
+    }
+
+    /// Reads the name of a command, after its backslash.
+    ///
+    /// The name is a word, or a single character: `\\` is the `\` command.
+    fn read_command_name(&mut self) -> String {
+        let mut name: String = String::default();
+        while let Some(c) = self.chars.next_if(char::is_ascii_alphabetic) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            if let Some(c) = self.chars.next() {
+                name.push(c);
+            }
+        }
+        name
+    }
+
+    /// Reads the name of the command coming next, without consuming it.
+    fn peek_command(&self) -> String {
+        let mut chars: Peekable<Chars> = self.chars.clone();
+        chars.next();
+        chars.take_while(char::is_ascii_alphabetic).collect()
+    }
+
+    /// Reads the delimiter following `\left` or `\right`.
+    fn read_delimiter(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some('.') => Ok(String::default()),
+            Some('\\') => {
+                let name: String = self.read_command_name();
+                OPERATORS
+                    .iter()
+                    .find(|(command, _)| *command == name)
+                    .map(|(_, operator)| escape_html(operator))
+                    .ok_or_else(|| format!("unsupported delimiter `\\{name}`"))
+            }
+            Some(c) => Ok(escape_html(&c.to_string())),
+            None => Err("missing delimiter".to_string()),
+        }
+    }
+
+    /// Reads the raw text between braces, for the text commands.
+    fn read_braced_text(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'{').is_none() {
+            return Err("missing `{`".to_string());
+        }
+        let mut text: String = String::default();
+        let mut depth: usize = 0;
+        for c in self.chars.by_ref() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(text),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        Err("missing `}`".to_string())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_math() {
+        let mathml: String = render_math(r"\frac{a+b}{2} \times x^2", false).unwrap();
+        assert_eq!(
+            mathml,
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"inline\"><semantics><mrow><mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mrow><mn>2</mn></mrow></mfrac><mo>×</mo><msup><mi>x</mi><mn>2</mn></msup></mrow><annotation encoding=\"application/x-tex\">\\frac{a+b}{2} \\times x^2</annotation></semantics></math>"
+        );
+
+        let mathml: String = render_math(r"\sum_{i=1}^{n} \text{price}_i \cdot \left( 1 - \delta \right)", true).unwrap();
+        assert!(mathml.contains("<munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mrow><mi>n</mi></mrow></munderover>"));
+        assert!(mathml.contains("<msub><mtext>price</mtext><mi>i</mi></msub>"));
+        assert!(mathml.contains("<mrow><mo fence=\"true\">(</mo><mn>1</mn><mo>−</mo><mi>δ</mi><mo fence=\"true\">)</mo></mrow>"));
+    }
+
+    #[test]
+    fn test_render_math_unsupported() {
+        assert_eq!(render_math(r"\begin{matrix} a \end{matrix}", true), Err("unsupported command `\\begin`".to_string()));
+        assert_eq!(render_math(r"\frac{a}{b", false), Err("missing closing delimiter".to_string()));
+        assert_eq!(render_math(r"a = b \\ c = d", true), Err("unsupported line break `\\\\`".to_string()));
+    }
+}
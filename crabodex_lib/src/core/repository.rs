@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::{Config, Forge};
+
+/// A repository the documentation comes from.
+/// - `name`: The name of the repository.
+/// - `description`: The description of the repository.
+/// - `url`: The URL of the repository.
+/// - `commit_hash`: The commit the documentation was generated from.
+/// - `source_ref`: The branch, tag or commit the source links point at.
+/// - `forge`: The forge hosting the repository, which gives the format of the links to it.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Repository {
+    pub name: String,
+    pub description: String,
+    pub url: String,
+    pub commit_hash: String,
+    pub source_ref: String,
+    // The exports made before the forge was exported are on GitHub
+    #[serde(default)]
+    pub forge: Forge,
+}
+
+impl Repository {
+    /// The configuration of the links to the repository: the given one, with the forge of the repository.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration of the documentation generation.
+    ///
+    /// # Returns
+    /// The configuration to build the links to the repository with.
+    ///
+    #[must_use]
+    pub fn link_config(&self, config: &Config) -> Config {
+        Config { forge: self.forge, ..config.clone() }
+    }
+}
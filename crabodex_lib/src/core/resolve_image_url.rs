@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::core::build_source_link::build_raw_link;
+use crate::core::config::{Config, ImageMode};
+use crate::core::resolve_relative_path::resolve_relative_path;
+
+/// The MIME types of the images, by extension.
+const IMAGE_MIME_TYPES: &[(&str, &str)] = &[
+    ("apng", "image/apng"),
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("gif", "image/gif"),
+    ("ico", "image/x-icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+];
+
+/// Resolves the URL of an image referenced in a markdown file, so it can be displayed from the generated page.
+///
+/// The local images are found relative to the markdown file. Depending on the image mode,
+/// they are embedded as base64 data URIs, or copied to the assets directory of the output
+/// directory, keeping their path in the root directory, and linked relative to the output
+/// directory. The URLs with a scheme, the absolute ones and the anchors are left as is.
+///
+/// # Arguments
+/// * `url` - The URL of the image, as written in the markdown file.
+/// * `file_path` - The path of the markdown file, relative to the root directory.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// * `Ok(Some(url))` - The new URL of the image.
+/// * `Ok(None)` - If the URL is left as is.
+/// * `Err(message)` - If the image can not be embedded or copied.
+///
+/// # Errors
+/// If the image does not exist, is outside the root directory, has an unknown type,
+/// or is larger than the size limit or not of the type of its extension when it is embedded.
+///
+pub fn resolve_image_url(
+    url: &str,
+    file_path: &str,
+    root_directory: &Path,
+    config: &Config,
+) -> Result<Option<String>, String> {
+    if config.image_mode == ImageMode::Keep || !is_local_url(url) {
+        return Ok(None);
+    }
+
+    let path: String = decode_percent(url.split(['?', '#']).next().unwrap_or_default());
+    let relative_path: PathBuf = resolve_relative_path(file_path, &path)
+        .ok_or_else(|| "it is outside the root directory".to_string())?;
+    let full_path: PathBuf = root_directory.join(&relative_path);
+    if !full_path.is_file() {
+        return Err("the file does not exist".to_string());
+    }
+
+    if config.image_mode == ImageMode::Copy {
+        // The page is written to the output directory, the copy is linked relative to it
+        let asset_path: PathBuf = config.assets_directory.join(&relative_path);
+        let copy_path: PathBuf = config.output_directory.join(&asset_path);
+        if let Some(parent) = copy_path.parent() {
+            fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+        }
+        fs::copy(&full_path, &copy_path).map_err(|error| error.to_string())?;
+        return Ok(Some(encode_percent(&asset_path.to_string_lossy().replace('\\', "/"))));
+    }
+
+    let extension: String = relative_path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let (_, mime_type) = IMAGE_MIME_TYPES
+        .iter()
+        .find(|(image_extension, _)| *image_extension == extension)
+        .ok_or_else(|| format!("the image type `{extension}` is unknown"))?;
+    let bytes: Vec<u8> = fs::read(&full_path).map_err(|error| error.to_string())?;
+    if bytes.len() > config.max_image_size {
+        return Err(format!(
+            "it is {} bytes, more than the limit of {} bytes to embed it",
+            bytes.len(),
+            config.max_image_size
+        ));
+    }
+    // The extension can lie, the image would then be embedded with a wrong type
+    if !has_image_signature(&bytes, mime_type) {
+        return Err(format!("its content is not an image of type `{mime_type}`"));
+    }
+
+    Ok(Some(format!("data:{mime_type};base64,{}", STANDARD.encode(bytes))))
+}
+
+/// Resolves the URL of an image referenced in a markdown file of another repository, to the raw
+/// file in that repository.
+///
+/// The sections imported from the export of another repository have their images in it, not
+/// in the root directory. Their local images are linked at the ref of the repository, in the
+/// format of its forge. The URLs with a scheme, the absolute ones and the anchors are left as is.
+///
+/// # Arguments
+/// * `url` - The URL of the image, as written in the markdown file.
+/// * `file_path` - The path of the markdown file, relative to the root of the repository.
+/// * `repository` - The URL of the repository, and the ref the links point at.
+/// * `config` - The configuration of the links to the repository.
+///
+/// # Returns
+/// * `Ok(Some(url))` - The URL of the raw image in the repository.
+/// * `Ok(None)` - If the URL is left as is.
+/// * `Err(message)` - If the image is outside the repository.
+///
+/// # Errors
+/// If the image is outside the root of the repository.
+///
+pub fn rebase_image_url(url: &str, file_path: &str, repository: (&str, &str), config: &Config) -> Result<Option<String>, String> {
+    if !is_local_url(url) {
+        return Ok(None);
+    }
+
+    // The query and the fragment of the URL are kept on the rebased one
+    let (path, suffix): (&str, &str) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+    let relative_path: PathBuf = resolve_relative_path(file_path, path)
+        .ok_or_else(|| "it is outside the root directory".to_string())?;
+    let relative_path: String = relative_path.to_string_lossy().replace('\\', "/");
+    Ok(Some(format!("{}{suffix}", build_raw_link(repository.0, repository.1, &relative_path, config))))
+}
+
+/// Whether the first bytes of a file are the signature of its type of image.
+fn has_image_signature(bytes: &[u8], mime_type: &str) -> bool {
+    match mime_type {
+        "image/apng" | "image/png" => bytes.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/avif" => bytes.get(4..8) == Some(b"ftyp") && matches!(bytes.get(8..12), Some(b"avif" | b"avis")),
+        "image/bmp" => bytes.starts_with(b"BM"),
+        "image/gif" => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+        "image/x-icon" => bytes.starts_with(&[0, 0, 1, 0]),
+        "image/jpeg" => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/webp" => bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP"),
+        // An SVG image is a text document with an `<svg` element, after its prolog
+        "image/svg+xml" => String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]).contains("<svg"),
+        _ => false,
+    }
+}
+
+/// Whether a URL points at a local file: it has no scheme, is not absolute and is not an anchor.
+fn is_local_url(url: &str) -> bool {
+    let has_scheme: bool = url
+        .find(':')
+        .is_some_and(|index| !url[..index].contains(['/', '?', '#']));
+    !has_scheme && !url.starts_with(['/', '#']) && !url.is_empty()
+}
+
+/// Escapes the characters of a URL path that are not allowed in it as `%XX`.
+fn encode_percent(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Decodes the `%XX` escapes of a URL path.
+fn decode_percent(path: &str) -> String {
+    let bytes: &[u8] = path.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index: usize = 0;
+
+    while index < bytes.len() {
+        let escaped: Option<u8> = (bytes[index] == b'%')
+            .then(|| path.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_image_url() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+        let config: Config = Config { max_image_size: 8, ..Config::default() };
+
+        assert_eq!(
+            resolve_image_url("../images/flow%20chart.svg", "docs/pricing.md", &test_dir, &config),
+            Ok(Some("data:image/svg+xml;base64,PHN2Zy8+".to_string()))
+        );
+        assert_eq!(resolve_image_url("https://example.com/flow.png", "docs/pricing.md", &test_dir, &config), Ok(None));
+        assert_eq!(
+            resolve_image_url("../images/big.png", "docs/pricing.md", &test_dir, &config),
+            Err("it is 16 bytes, more than the limit of 8 bytes to embed it".to_string())
+        );
+        assert_eq!(
+            resolve_image_url("../images/big.png", "docs/pricing.md", &test_dir, &Config::default()),
+            Err("its content is not an image of type `image/png`".to_string())
+        );
+        assert_eq!(
+            resolve_image_url("missing.png", "docs/pricing.md", &test_dir, &config),
+            Err("the file does not exist".to_string())
+        );
+
+        // The copies are written to a directory of their own, removed afterwards
+        let output_directory: PathBuf = std::env::temp_dir().join(format!("crabodex_test_resolve_image_url_{}", std::process::id()));
+        let config: Config = Config { image_mode: ImageMode::Copy, output_directory: output_directory.clone(), ..Config::default() };
+        let copy_url: Option<String> = resolve_image_url("../images/flow%20chart.svg", "docs/pricing.md", &test_dir, &config).unwrap();
+
+        assert_eq!(copy_url, Some("assets/images/flow%20chart.svg".to_string()));
+        assert_eq!(fs::read_to_string(output_directory.join("assets/images/flow chart.svg")).unwrap(), "<svg/>");
+        fs::remove_dir_all(output_directory).unwrap();
+    }
+
+    #[test]
+    fn test_rebase_image_url() {
+        let repository: (&str, &str) = ("https://gitlab.com/acme/billing", "ebb34e7");
+        let config: Config = Config { forge: crate::core::config::Forge::GitLab, ..Config::default() };
+
+        assert_eq!(
+            rebase_image_url("../images/flow%20chart.svg?v=2", "docs/pricing.md", repository, &config),
+            Ok(Some("https://gitlab.com/acme/billing/-/raw/ebb34e7/images/flow%20chart.svg?v=2".to_string()))
+        );
+        assert_eq!(rebase_image_url("https://example.com/flow.png", "docs/pricing.md", repository, &config), Ok(None));
+        assert_eq!(
+            rebase_image_url("../../flow.png", "docs/pricing.md", repository, &config),
+            Err("it is outside the root directory".to_string())
+        );
+    }
+}
@@ -0,0 +1,604 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::Event;
+
+use crate::core::constants::MAX_INCLUDE_DEPTH;
+use crate::core::diagnostic::Diagnostic;
+use crate::core::resolve_relative_path::resolve_relative_path;
+use crate::core::strip_front_matter::{content_lines, strip_front_matter};
+use crate::DocNode;
+
+/// The prefix of the include directive: `{{#include path}}`.
+const INCLUDE_DIRECTIVE_PREFIX: &str = "{{#include ";
+
+/// The suffix of the include directive.
+const INCLUDE_DIRECTIVE_SUFFIX: &str = "}}";
+
+/// The extensions of the included files embedded as markdown, whatever their case.
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+/// The marker starting a region in a source file: `// crabodex:start name`.
+const REGION_START_MARKER: &str = "crabodex:start";
+
+/// The marker ending a region in a source file: `// crabodex:end`.
+const REGION_END_MARKER: &str = "crabodex:end";
+
+/// The comment opening the content of an included markdown file, followed by its path.
+const TRANSCLUSION_START_MARKER: &str = "<!-- crabodex:transclusion-start ";
+
+/// The comment closing the content of an included markdown file.
+const TRANSCLUSION_END_MARKER: &str = "<!-- crabodex:transclusion-end -->";
+
+/// A comment around the content of an included markdown file.
+/// - `Start`: The comment opening the content, with the path of the file and the lines of the content in it.
+/// - `End`: The comment closing the content.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransclusionMarker<'a> {
+    Start { path: &'a str, lines: Option<(usize, usize)> },
+    End,
+}
+
+/// The part of a source file to include.
+enum Selection {
+    Whole,
+    Region(String),
+    Lines(usize, usize),
+}
+
+/// A code snippet taken from a source file.
+/// - `path`: The path of the source file, relative to the root directory.
+/// - `language`: The language of the code, from the extension of the file.
+/// - `code`: The code of the snippet.
+/// - `lines`: The first and last lines of the snippet in the source file.
+///
+struct Snippet {
+    path: String,
+    language: String,
+    code: String,
+    lines: (usize, usize),
+}
+
+/// Resolves the code snippets and the markdown files included in the markdown content of the document structure recursively.
+///
+/// A snippet is included with a `{{#include path}}` directive on its own line, or with
+/// a fenced code block whose info string has a `file=path` attribute. The path is
+/// relative to the markdown file. It can be followed by `:region` to only include
+/// the lines between the `crabodex:start region` and `crabodex:end` markers of the
+/// file, or by `:10:30` to only include some lines. In a fenced code block, the
+/// `region=name` and `lines=10-30` attributes do the same.
+///
+/// The included snippets are fenced code blocks whose info string has the language
+/// of the file, and `source` and `lines` attributes to link back to the source.
+/// A markdown file included with a `{{#include path.md}}` directive, or with the
+/// `.markdown` extension in any case, is embedded without its Front Matter, between
+/// two comments holding its path and the lines of its content. Its regions and lines
+/// are selected like the ones of a snippet. Its own
+/// directives are resolved relative to it. The inclusion cycles and the inclusions
+/// nested deeper than `MAX_INCLUDE_DEPTH` are not resolved.
+///
+/// The directives that can not be resolved are left as is and raise a warning.
+///
+/// # Arguments
+/// * `node` - The current node in the document structure.
+/// * `root_directory` - The root directory where the markdown files are stored.
+/// * `diagnostics` - The list the warnings are added to.
+///
+pub fn resolve_includes(node: &mut DocNode, root_directory: &Path, diagnostics: &mut Vec<Diagnostic>) {
+    if let (Some(content), Some(file_path)) = (&node.content, &node.file_path) {
+        let mut included_files: Vec<String> = vec![file_path.clone()];
+        node.content = Some(expand_includes(content, root_directory, &mut included_files, diagnostics));
+    }
+
+    let mut children: Vec<&mut DocNode> = node.children.values_mut().collect();
+    children.sort_by(|a, b| a.path.cmp(&b.path));
+    for child in children {
+        resolve_includes(child, root_directory, diagnostics);
+    }
+}
+
+/// Finds the value of a `key=value` attribute in the info string of a fenced code block.
+///
+/// # Arguments
+/// * `info` - The info string of the code block.
+/// * `key` - The name of the attribute.
+///
+/// # Returns
+/// The value of the attribute, if any.
+///
+#[must_use]
+pub fn fence_attribute<'a>(info: &'a str, key: &str) -> Option<&'a str> {
+    info.split_whitespace()
+        .find_map(|attribute| attribute.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// Finds the comments around the content of an included markdown file.
+///
+/// # Arguments
+/// * `html` - A raw HTML block of the markdown content.
+///
+/// # Returns
+/// The comment, or `None` if the block is not one of these comments.
+///
+#[must_use]
+pub fn transclusion_marker(html: &str) -> Option<TransclusionMarker<'_>> {
+    let html: &str = html.trim();
+    if html == TRANSCLUSION_END_MARKER {
+        return Some(TransclusionMarker::End);
+    }
+
+    let marker: &str = html.strip_prefix(TRANSCLUSION_START_MARKER)?.strip_suffix("-->")?.trim();
+    // The lines of the content come last, after the path
+    match marker.rsplit_once(' ').map(|(path, lines)| (path, parse_line_range(lines))) {
+        Some((path, Some(lines))) => Some(TransclusionMarker::Start { path: path.trim(), lines: Some(lines) }),
+        _ => Some(TransclusionMarker::Start { path: marker, lines: None }),
+    }
+}
+
+/// Finds the transclusion markers that open and close each other, nested like the inclusions.
+///
+/// The markers written in the markdown files themselves are left unpaired.
+///
+/// # Arguments
+/// * `events` - The events of a markdown content.
+///
+/// # Returns
+/// The indexes of the paired markers in the events.
+///
+#[must_use]
+pub fn paired_transclusion_markers(events: &[Event]) -> HashSet<usize> {
+    let mut paired: HashSet<usize> = HashSet::new();
+    let mut open_markers: Vec<usize> = Vec::new();
+
+    for (index, event) in events.iter().enumerate() {
+        let Event::Html(raw) = event else { continue };
+        match transclusion_marker(raw) {
+            Some(TransclusionMarker::Start { .. }) => open_markers.push(index),
+            Some(TransclusionMarker::End) => {
+                if let Some(start) = open_markers.pop() {
+                    paired.extend([start, index]);
+                }
+            }
+            None => {}
+        }
+    }
+
+    paired
+}
+
+/// Follows a paired transclusion marker to know which file the content after it comes from.
+///
+/// # Arguments
+/// * `file_paths` - The files the content comes from: the file of the node, then the included ones.
+/// * `marker` - The marker opening or closing the content of an included file.
+///
+pub fn follow_transclusion_marker(file_paths: &mut Vec<String>, marker: &TransclusionMarker) {
+    match marker {
+        TransclusionMarker::Start { path, .. } => file_paths.push((*path).to_string()),
+        // The file of the node stays the first one
+        TransclusionMarker::End => {
+            if file_paths.len() > 1 {
+                file_paths.pop();
+            }
+        }
+    }
+}
+
+/// Replaces the include directives of a markdown content with what they include.
+///
+/// The last of the included files is the one the content comes from.
+fn expand_includes(
+    content: &str,
+    root_directory: &Path,
+    included_files: &mut Vec<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    let file_path: String = included_files.last().cloned().unwrap_or_default();
+    let file_path: &str = &file_path;
+    let mut expanded: Vec<String> = Vec::new();
+    let mut lines = content.lines();
+    // The opening fence of the code block the current line is in
+    let mut code_fence: Option<String> = None;
+
+    while let Some(line) = lines.next() {
+        let trimmed: &str = line.trim();
+
+        if let Some(fence) = &code_fence {
+            if trimmed.starts_with(fence.as_str()) && trimmed.trim_start_matches(fence.chars().next().unwrap_or('`')).is_empty() {
+                code_fence = None;
+            }
+            expanded.push(line.to_string());
+            continue;
+        }
+
+        if let Some(target) = trimmed
+            .strip_prefix(INCLUDE_DIRECTIVE_PREFIX)
+            .and_then(|directive| directive.strip_suffix(INCLUDE_DIRECTIVE_SUFFIX))
+        {
+            let (path, selection): (&str, Selection) = parse_target(target.trim());
+            let included: Result<String, String> = if is_markdown_path(path) {
+                include_markdown(path, selection, root_directory, included_files, diagnostics)
+            } else {
+                include_snippet(path, selection, file_path, root_directory).map(|snippet| fenced_snippet(&snippet))
+            };
+            match included {
+                Ok(included) => expanded.push(included),
+                Err(error) => {
+                    diagnostics.push(Diagnostic::new(Some(file_path), &format!("could not include `{}`: {}", target.trim(), error)));
+                    expanded.push(line.to_string());
+                }
+            }
+            continue;
+        }
+
+        let fence_length: usize = trimmed.len() - trimmed.trim_start_matches(['`', '~']).len();
+        if fence_length < 3 {
+            expanded.push(line.to_string());
+            continue;
+        }
+        let fence: &str = &trimmed[..fence_length];
+        let info: &str = &trimmed[fence_length..];
+
+        let Some(target) = fence_attribute(info, "file") else {
+            code_fence = Some(fence.to_string());
+            expanded.push(line.to_string());
+            continue;
+        };
+
+        // The body of the code block is replaced by the snippet
+        let body: Vec<&str> = lines
+            .by_ref()
+            .take_while(|line| !line.trim().starts_with(fence))
+            .collect();
+        let (path, mut selection): (&str, Selection) = parse_target(target);
+        if let Some(region) = fence_attribute(info, "region") {
+            selection = Selection::Region(region.to_string());
+        }
+        if let Some((start, end)) = fence_attribute(info, "lines").and_then(parse_line_range) {
+            selection = Selection::Lines(start, end);
+        }
+
+        match include_snippet(path, selection, file_path, root_directory) {
+            Ok(mut snippet) => {
+                // An explicit language in the info string wins over the extension
+                if let Some(language) = info.split_whitespace().next().filter(|word| !word.contains('=')) {
+                    snippet.language = language.to_string();
+                }
+                expanded.push(fenced_snippet(&snippet));
+            }
+            Err(error) => {
+                diagnostics.push(Diagnostic::new(Some(file_path), &format!("could not include `{target}`: {error}")));
+                expanded.push(line.to_string());
+                expanded.extend(body.iter().map(ToString::to_string));
+                expanded.push(fence.to_string());
+            }
+        }
+    }
+
+    expanded.join("\n")
+}
+
+/// Whether an included file is a markdown file, embedded instead of included as a snippet.
+fn is_markdown_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| MARKDOWN_EXTENSIONS.iter().any(|markdown| extension.eq_ignore_ascii_case(markdown)))
+}
+
+/// Reads an included markdown file, or a part of it, and resolves its own include directives.
+///
+/// The whole file is included without its Front Matter.
+fn include_markdown(
+    path: &str,
+    selection: Selection,
+    root_directory: &Path,
+    included_files: &mut Vec<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<String, String> {
+    let file_path: &str = included_files.last().map_or("", String::as_str);
+    let relative_path: String = resolve_relative_path(file_path, path)
+        .ok_or_else(|| format!("`{path}` is outside the root directory"))?
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    if included_files.contains(&relative_path) {
+        return Err(format!("inclusion cycle `{} > {}`", included_files.join(" > "), relative_path));
+    }
+    if included_files.len() > MAX_INCLUDE_DEPTH {
+        return Err(format!("more than {MAX_INCLUDE_DEPTH} nested inclusions"));
+    }
+    let content: String = fs::read_to_string(root_directory.join(&relative_path))
+        .map_err(|error| format!("could not read `{path}`: {error}"))?;
+
+    let (included, lines): (String, Option<(usize, usize)>) = match selection {
+        Selection::Whole => (strip_front_matter(&content).to_string(), content_lines(&content)),
+        selection => {
+            let file_lines: Vec<&str> = content.lines().collect();
+            let (start, end): (usize, usize) = select_lines(&file_lines, selection, path)?;
+            (without_region_markers(&file_lines[start - 1..end]).join("\n"), Some((start, end)))
+        }
+    };
+
+    included_files.push(relative_path.clone());
+    let expanded: String = expand_includes(&included, root_directory, included_files, diagnostics);
+    included_files.pop();
+
+    let lines: String = lines.map_or(String::default(), |(start, end)| format!(" {start}-{end}"));
+    // The blank lines keep the comments in their own HTML blocks
+    Ok(format!("{TRANSCLUSION_START_MARKER}{relative_path}{lines} -->\n\n{expanded}\n\n{TRANSCLUSION_END_MARKER}"))
+}
+
+/// Splits the target of an include into the path of the file and the part to include.
+///
+/// `path:name` selects a region, `path:10:30` selects lines.
+fn parse_target(target: &str) -> (&str, Selection) {
+    let Some((path, selector)) = target.split_once(':') else {
+        return (target, Selection::Whole);
+    };
+
+    match parse_line_range(selector) {
+        Some((start, end)) => (path, Selection::Lines(start, end)),
+        None => (path, Selection::Region(selector.to_string())),
+    }
+}
+
+/// Parses a range of lines written `10-30` or `10:30`.
+fn parse_line_range(range: &str) -> Option<(usize, usize)> {
+    let (start, end) = range.split_once(['-', ':'])?;
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()?))
+}
+
+/// Reads the part of a source file to include.
+fn include_snippet(
+    path: &str,
+    selection: Selection,
+    file_path: &str,
+    root_directory: &Path,
+) -> Result<Snippet, String> {
+    let relative_path: PathBuf = resolve_relative_path(file_path, path)
+        .ok_or_else(|| format!("`{path}` is outside the root directory"))?;
+    let source: String = fs::read_to_string(root_directory.join(&relative_path))
+        .map_err(|error| format!("could not read `{path}`: {error}"))?;
+    let lines: Vec<&str> = source.lines().collect();
+
+    let (start, end): (usize, usize) = select_lines(&lines, selection, path)?;
+    let code_lines: Vec<&str> = without_region_markers(&lines[start - 1..end]);
+    let indentation: usize = code_lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let code: String = code_lines
+        .iter()
+        .map(|line| line.get(indentation..).unwrap_or_default())
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    Ok(Snippet {
+        path: relative_path.to_string_lossy().replace('\\', "/"),
+        language: relative_path
+            .extension()
+            .map_or("text".to_string(), |extension| extension.to_string_lossy().to_lowercase()),
+        code,
+        lines: (start, end),
+    })
+}
+
+/// Finds the first and last lines of the part of a file to include, numbered from 1.
+fn select_lines(lines: &[&str], selection: Selection, path: &str) -> Result<(usize, usize), String> {
+    match selection {
+        Selection::Whole => Ok((1, lines.len())),
+        Selection::Lines(start, end) => {
+            if start == 0 || start > end || end > lines.len() {
+                return Err(format!("lines {start}-{end} are out of range, `{path}` has {} lines", lines.len()));
+            }
+            Ok((start, end))
+        }
+        Selection::Region(name) => find_region(lines, &name)
+            .ok_or_else(|| format!("region `{name}` not found in `{path}`")),
+    }
+}
+
+/// Removes the markers of the nested regions from the lines of an included part.
+fn without_region_markers<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    lines.iter().copied().filter(|line| region_marker(line).is_none()).collect()
+}
+
+/// Finds the first and last lines of the content of a region, numbered from 1.
+///
+/// The regions can be nested, a `crabodex:end` marker closes the last opened one.
+fn find_region(lines: &[&str], name: &str) -> Option<(usize, usize)> {
+    let start: usize = lines
+        .iter()
+        .position(|line| region_marker(line) == Some((true, Some(name))))?;
+    let mut depth: usize = 0;
+
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        match region_marker(line) {
+            Some((true, _)) => depth += 1,
+            Some((false, _)) if depth == 0 => return Some((start + 2, index)),
+            Some((false, _)) => depth -= 1,
+            None => {}
+        }
+    }
+
+    None
+}
+
+/// Finds a region marker in a line of code, whatever the comment syntax.
+///
+/// Returns whether it starts a region, and the name of the region if any.
+fn region_marker(line: &str) -> Option<(bool, Option<&str>)> {
+    let (is_start, rest): (bool, &str) = match line.find(REGION_START_MARKER) {
+        Some(index) => (true, &line[index + REGION_START_MARKER.len()..]),
+        None => (false, &line[line.find(REGION_END_MARKER)? + REGION_END_MARKER.len()..]),
+    };
+    let name: Option<&str> = rest
+        .split_whitespace()
+        .next()
+        .filter(|name| name.chars().any(|c| c.is_alphanumeric()));
+
+    Some((is_start, name))
+}
+
+/// Writes a snippet as a fenced code block, with a fence longer than the backticks in the code.
+fn fenced_snippet(snippet: &Snippet) -> String {
+    let mut longest_backticks: usize = 0;
+    let mut backticks: usize = 0;
+    for c in snippet.code.chars() {
+        backticks = if c == '`' { backticks + 1 } else { 0 };
+        longest_backticks = longest_backticks.max(backticks);
+    }
+    let fence: String = "`".repeat(longest_backticks.max(2) + 1);
+
+    format!(
+        "{fence}{} source={} lines={}-{}\n{}\n{fence}",
+        snippet.language, snippet.path, snippet.lines.0, snippet.lines.1, snippet.code
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_includes() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Pricing", "Pricing");
+        node.file_path = Some("sub_dir_1/pricing.md".to_string());
+        node.content = Some(
+            [
+                "{{#include ../sub_dir_2/pricing.rs:compute_discount}}",
+                "```rust file=../sub_dir_2/pricing.rs lines=1-2",
+                "```",
+                "{{#include ../sub_dir_2/pricing.rs:missing}}",
+                "{{#include ../../pricing.rs}}",
+            ]
+            .join("\n"),
+        );
+        root.children.insert("Pricing".to_string(), node);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        resolve_includes(&mut root, &test_dir, &mut diagnostics);
+
+        assert_eq!(
+            root.children["Pricing"].content.as_deref(),
+            Some(
+                [
+                    "```rs source=sub_dir_2/pricing.rs lines=5-10",
+                    "pub fn compute_discount(price: u32, rate: u32) -> u32 {",
+                    "    let discount: u32 = price * rate / 100;",
+                    "    discount.min(MAX_DISCOUNT)",
+                    "}",
+                    "```",
+                    "```rust source=sub_dir_2/pricing.rs lines=1-2",
+                    "/// The maximum discount, in cents.",
+                    "pub const MAX_DISCOUNT: u32 = 5_000;",
+                    "```",
+                    "{{#include ../sub_dir_2/pricing.rs:missing}}",
+                    "{{#include ../../pricing.rs}}",
+                ]
+                .join("\n")
+                .as_str()
+            )
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(Some("sub_dir_1/pricing.md"), "could not include `../sub_dir_2/pricing.rs:missing`: region `missing` not found in `../sub_dir_2/pricing.rs`"),
+                Diagnostic::new(Some("sub_dir_1/pricing.md"), "could not include `../../pricing.rs`: `../../pricing.rs` is outside the root directory"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_includes_markdown() {
+        // The included files are out of the documentation of the test files
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files")
+            .join("ignored_test_files");
+
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Privacy", "Privacy");
+        node.file_path = Some("privacy.md".to_string());
+        node.content = Some("Intro\n\n{{#include legal/gdpr.md}}".to_string());
+        root.children.insert("Privacy".to_string(), node);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        resolve_includes(&mut root, &test_dir, &mut diagnostics);
+
+        assert_eq!(
+            root.children["Privacy"].content.as_deref(),
+            Some(
+                [
+                    "Intro",
+                    "",
+                    "<!-- crabodex:transclusion-start legal/gdpr.md 6-8 -->",
+                    "",
+                    "We keep nothing.",
+                    "",
+                    "<!-- crabodex:transclusion-start legal/notice.md 1-2 -->",
+                    "",
+                    "See the notice.",
+                    "{{#include gdpr.md}}",
+                    "",
+                    "<!-- crabodex:transclusion-end -->",
+                    "",
+                    "<!-- crabodex:transclusion-end -->",
+                ]
+                .join("\n")
+                .as_str()
+            )
+        );
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::new(Some("legal/notice.md"), "could not include `gdpr.md`: inclusion cycle `privacy.md > legal/gdpr.md > legal/notice.md > legal/gdpr.md`")]
+        );
+    }
+
+    #[test]
+    fn test_resolve_includes_markdown_region() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files")
+            .join("ignored_test_files");
+
+        let mut root: DocNode = DocNode::new("Documentation", "");
+        let mut node: DocNode = DocNode::new("Terms", "Terms");
+        node.file_path = Some("terms.md".to_string());
+        node.content = Some("{{#include legal/terms.Markdown:refunds}}\n{{#include legal/terms.Markdown:7:7}}".to_string());
+        root.children.insert("Terms".to_string(), node);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        resolve_includes(&mut root, &test_dir, &mut diagnostics);
+
+        assert_eq!(
+            root.children["Terms"].content.as_deref(),
+            Some(
+                [
+                    "<!-- crabodex:transclusion-start legal/terms.Markdown 4-4 -->",
+                    "",
+                    "Refunds take two weeks.",
+                    "",
+                    "<!-- crabodex:transclusion-end -->",
+                    "<!-- crabodex:transclusion-start legal/terms.Markdown 7-7 -->",
+                    "",
+                    "Other terms.",
+                    "",
+                    "<!-- crabodex:transclusion-end -->",
+                ]
+                .join("\n")
+                .as_str()
+            )
+        );
+        assert!(diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves a path written in a markdown file, relative to that file, into a path
+/// relative to the root directory.
+///
+/// # Arguments
+/// * `file_path` - The path of the markdown file, relative to the root directory.
+/// * `path` - The path written in the markdown file.
+///
+/// # Returns
+/// The path relative to the root directory, or `None` if it goes outside of it.
+///
+#[must_use]
+pub fn resolve_relative_path(file_path: &str, path: &str) -> Option<PathBuf> {
+    let mut resolved: PathBuf = PathBuf::new();
+
+    for component in Path::new(file_path).parent()?.join(path).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(resolved)
+}
@@ -0,0 +1,338 @@
+use crate::core::escape_html::escape_html;
+
+/// The tags kept by the sanitizer. Every other tag is stripped.
+const ALLOWED_TAGS: &[&str] = &[
+    "a", "abbr", "b", "blockquote", "br", "caption", "cite", "code", "dd", "del", "details",
+    "div", "dl", "dt", "em", "figcaption", "figure", "h1", "h2", "h3", "h4", "h5", "h6", "hr",
+    "i", "img", "ins", "kbd", "li", "mark", "ol", "p", "pre", "q", "s", "samp", "small", "span",
+    "strong", "sub", "summary", "sup", "table", "tbody", "td", "tfoot", "th", "thead", "tr", "u",
+    "ul",
+];
+
+/// The attributes kept by the sanitizer on the allowed tags.
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "align", "alt", "colspan", "height", "href", "id", "lang", "open", "rowspan", "src",
+    "title", "width",
+];
+
+/// The attributes holding a URL, whose scheme must be checked.
+const URL_ATTRIBUTES: &[&str] = &["href", "src"];
+
+/// The URL schemes allowed in the URL attributes.
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// The named character references decoded in the attribute values, the other ones are kept as text.
+const NAMED_REFERENCES: &[(&str, char)] = &[
+    ("amp", '&'), ("apos", '\''), ("colon", ':'), ("gt", '>'), ("lpar", '('), ("lt", '<'),
+    ("newline", '\n'), ("nbsp", '\u{a0}'), ("num", '#'), ("period", '.'), ("quot", '"'),
+    ("rpar", ')'), ("semi", ';'), ("sol", '/'), ("tab", '\t'),
+];
+
+/// The tags stripped along with everything they contain.
+const DROPPED_CONTENT_TAGS: &[&str] = &[
+    "embed", "iframe", "noscript", "object", "script", "style", "template", "textarea", "title",
+];
+
+/// Sanitizes the raw HTML fragments of a markdown document.
+///
+/// The raw HTML comes from pulldown-cmark as a sequence of fragments, so a
+/// tag whose content must be dropped (ex: `<script>`) can span several of
+/// them. The sanitizer keeps track of it between calls to `sanitize`.
+///
+/// - `dropped_tag`: The tag whose content is currently being dropped.
+/// - `stripped`: The description of everything that has been stripped.
+///
+#[derive(Debug, Default)]
+pub struct HtmlSanitizer {
+    dropped_tag: Option<String>,
+    stripped: Vec<String>,
+}
+
+impl HtmlSanitizer {
+    /// Sanitizes a raw HTML fragment.
+    ///
+    /// # Arguments
+    /// * `fragment` - The raw HTML fragment.
+    ///
+    /// # Returns
+    /// The fragment with only the allowed tags and attributes.
+    ///
+    pub fn sanitize(&mut self, fragment: &str) -> String {
+        let mut html: String = String::with_capacity(fragment.len());
+        let mut rest: &str = fragment;
+
+        loop {
+            if let Some(dropped_tag) = &self.dropped_tag {
+                let closing_tag: String = format!("</{dropped_tag}");
+                match rest.to_ascii_lowercase().find(&closing_tag) {
+                    Some(index) => {
+                        rest = rest[index..].find('>').map_or("", |end| &rest[index + end + 1..]);
+                        self.dropped_tag = None;
+                    }
+                    None => return html,
+                }
+            }
+
+            let Some(start) = rest.find('<') else {
+                html.push_str(rest);
+                return html;
+            };
+            html.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            if rest.starts_with("<!--") {
+                rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+                self.strip("comment");
+                continue;
+            }
+
+            let is_tag: bool = rest[1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?');
+            let end: Option<usize> = if is_tag { find_tag_end(rest) } else { None };
+            let Some(end) = end else {
+                html.push_str("&lt;");
+                rest = &rest[1..];
+                continue;
+            };
+
+            self.push_tag(&mut html, &rest[1..end]);
+            rest = &rest[end + 1..];
+        }
+    }
+
+    /// The description of everything that has been stripped so far, without duplicates.
+    #[must_use]
+    pub fn stripped(&self) -> &[String] {
+        &self.stripped
+    }
+
+    fn strip(&mut self, description: &str) {
+        if !self.stripped.iter().any(|s| s == description) {
+            self.stripped.push(description.to_string());
+        }
+    }
+
+    fn push_tag(&mut self, html: &mut String, tag: &str) {
+        if tag.starts_with('!') || tag.starts_with('?') {
+            self.strip("declaration");
+            return;
+        }
+
+        let is_closing: bool = tag.starts_with('/');
+        let tag: &str = tag.trim_start_matches('/');
+        let name_end: usize = tag
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(tag.len());
+        let name: String = tag[..name_end].to_lowercase();
+
+        if !ALLOWED_TAGS.contains(&name.as_str()) {
+            if !is_closing {
+                self.strip(&format!("<{name}>"));
+                let is_self_closing: bool = tag.trim_end().ends_with('/');
+                if DROPPED_CONTENT_TAGS.contains(&name.as_str()) && !is_self_closing {
+                    self.dropped_tag = Some(name);
+                }
+            }
+            return;
+        }
+
+        if is_closing {
+            html.push_str(&format!("</{name}>"));
+            return;
+        }
+
+        html.push('<');
+        html.push_str(&name);
+        for (attribute, value) in parse_attributes(&tag[name_end..]) {
+            // The value is checked as the browser reads it, and escaped again when written
+            let value: Option<String> = value.as_deref().map(decode_character_references);
+            let is_allowed: bool = ALLOWED_ATTRIBUTES.contains(&attribute.as_str())
+                && (!URL_ATTRIBUTES.contains(&attribute.as_str())
+                    || value.as_deref().is_none_or(is_safe_url));
+            if !is_allowed {
+                self.strip(&format!("{attribute} on <{name}>"));
+                continue;
+            }
+            match value {
+                Some(value) => html.push_str(&format!(" {}=\"{}\"", attribute, escape_html(&value))),
+                None => html.push_str(&format!(" {attribute}")),
+            }
+        }
+        if tag.trim_end().ends_with('/') {
+            html.push_str(" /");
+        }
+        html.push('>');
+    }
+}
+
+/// Finds the index of the `>` closing the tag at the start of the string,
+/// ignoring the ones in quoted attribute values.
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses the attributes of a tag into lowercase names and optional values.
+fn parse_attributes(attributes: &str) -> Vec<(String, Option<String>)> {
+    let mut parsed: Vec<(String, Option<String>)> = Vec::new();
+    let mut chars = attributes.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace() || *c == '/') {
+            chars.next();
+        }
+        let mut name: String = String::default();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=' && *c != '/') {
+            name.push(c.to_ascii_lowercase());
+        }
+        if name.is_empty() {
+            return parsed;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        if chars.next_if_eq(&'=').is_none() {
+            parsed.push((name, None));
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut value: String = String::default();
+        match chars.next_if(|c| *c == '"' || *c == '\'') {
+            Some(quote) => {
+                for c in chars.by_ref() {
+                    if c == quote {
+                        break;
+                    }
+                    value.push(c);
+                }
+            }
+            None => {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+        }
+        parsed.push((name, Some(value)));
+    }
+}
+
+/// Decodes the character references of an attribute value, numeric or named, like a browser does.
+fn decode_character_references(value: &str) -> String {
+    let mut decoded: String = String::with_capacity(value.len());
+    let mut rest: &str = value;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let reference: Option<(char, usize)> = if let Some(number) = rest.strip_prefix('#') {
+            // The numeric references end at the first character that is not a digit, `;` is optional
+            let (digits, radix, prefix): (&str, u32, usize) = match number.strip_prefix(['x', 'X']) {
+                Some(hexadecimal) => (hexadecimal, 16, 2),
+                None => (number, 10, 1),
+            };
+            let length: usize = digits.find(|c: char| !c.is_digit(radix)).unwrap_or(digits.len());
+            u32::from_str_radix(&digits[..length], radix)
+                .ok()
+                .map(|code| (char::from_u32(code).unwrap_or('\u{fffd}'), prefix + length))
+        } else {
+            let length: usize = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+            NAMED_REFERENCES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&rest[..length]))
+                .map(|(_, c)| (*c, length))
+        };
+
+        match reference {
+            Some((c, length)) => {
+                decoded.push(c);
+                rest = &rest[length..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+            }
+            None => decoded.push('&'),
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Checks that a URL is relative or uses one of the allowed schemes.
+///
+/// # Arguments
+/// * `url` - The URL, with its character references decoded.
+///
+/// # Returns
+/// Whether the URL can not run a script when it is followed or loaded.
+///
+#[must_use]
+pub fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_lowercase();
+
+    match url.find(':') {
+        Some(index) if !url[..index].contains(['/', '?', '#']) => {
+            ALLOWED_SCHEMES.contains(&&url[..index])
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_html() {
+        let mut sanitizer: HtmlSanitizer = HtmlSanitizer::default();
+
+        let html: String = sanitizer.sanitize(
+            "<div class=\"box\" id=\"intro\"><a href=\"javascript:alert(1)\" title=\"x\">link</a><img src=\"flow.png\" onerror=\"alert(1)\"></div>",
+        );
+
+        assert_eq!(html, "<div id=\"intro\"><a title=\"x\">link</a><img src=\"flow.png\"></div>");
+        assert_eq!(
+            sanitizer.stripped(),
+            ["class on <div>", "href on <a>", "onerror on <img>"]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_html_decodes_character_references() {
+        let mut sanitizer: HtmlSanitizer = HtmlSanitizer::default();
+
+        let html: String = sanitizer.sanitize(
+            "<a href=\"&#106;avascript:alert(1)\">a</a><a href=\"javascript&colon;alert(1)\">b</a><a href=\"&#x6A;ava&Tab;script:alert(1)\">c</a><a href=\"/?a=1&amp;b=<2>\" title='\"x\"'>d</a>",
+        );
+
+        assert_eq!(html, "<a>a</a><a>b</a><a>c</a><a href=\"/?a=1&amp;b=&lt;2&gt;\" title=\"&quot;x&quot;\">d</a>");
+        assert_eq!(sanitizer.stripped(), ["href on <a>"]);
+    }
+
+    #[test]
+    fn test_sanitize_html_drops_script_across_fragments() {
+        let mut sanitizer: HtmlSanitizer = HtmlSanitizer::default();
+
+        let first: String = sanitizer.sanitize("<p>before</p><script>\n");
+        let second: String = sanitizer.sanitize("alert('<b>x</b>');\n");
+        let third: String = sanitizer.sanitize("</script><p>after 1 < 2</p><!-- note -->\n");
+
+        assert_eq!(first, "<p>before</p>");
+        assert_eq!(second, "");
+        assert_eq!(third, "<p>after 1 &lt; 2</p>\n");
+        assert_eq!(sanitizer.stripped(), ["<script>", "comment"]);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::core::constants::DEFAULT_FRONT_MATTER_PREFIX;
+
+/// Strips the Front Matter of a markdown file.
+///
+/// # Arguments
+/// * `file_content` - The content of the markdown file.
+///
+/// # Returns
+/// The content of the file after its Front Matter, trimmed. The whole content if it has no Front Matter.
+///
+#[must_use]
+pub fn strip_front_matter(file_content: &str) -> &str {
+    file_content
+        .strip_prefix(DEFAULT_FRONT_MATTER_PREFIX)
+        .and_then(|content| {
+            let end_index: usize = content.find(DEFAULT_FRONT_MATTER_PREFIX)?;
+            Some(&content[end_index + DEFAULT_FRONT_MATTER_PREFIX.len()..])
+        })
+        .unwrap_or(file_content)
+        .trim()
+}
+
+/// Finds the lines of a markdown file holding its content after the Front Matter.
+///
+/// # Arguments
+/// * `file_content` - The content of the markdown file.
+///
+/// # Returns
+/// The first and last lines of the content, numbered from 1, or `None` if there is no content.
+///
+#[must_use]
+pub fn content_lines(file_content: &str) -> Option<(usize, usize)> {
+    let content: &str = strip_front_matter(file_content);
+    if content.is_empty() {
+        return None;
+    }
+
+    // The content ends where the trimmed file ends
+    let content_start: usize = file_content.trim_end().len() - content.len();
+    let start_line: usize = file_content[..content_start].lines().count() + 1;
+    Some((start_line, start_line + content.lines().count() - 1))
+}
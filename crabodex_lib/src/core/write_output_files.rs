@@ -0,0 +1,62 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::diagnostic::Diagnostic;
+
+/// Writes the files of an output made of several files, creating the directories they are in.
+///
+/// # Arguments
+/// * `output_directory` - The directory the files are written to.
+/// * `files` - The paths of the files relative to the output directory, with their content.
+/// * `diagnostics` - The list the files that could not be written are added to.
+///
+/// # Returns
+/// The paths of the written files, one per line.
+///
+pub fn write_output_files(output_directory: &Path, files: Vec<(PathBuf, Vec<u8>)>, diagnostics: &mut Vec<Diagnostic>) -> String {
+    let mut written_files: String = String::default();
+
+    for (file_path, content) in files {
+        let full_path: PathBuf = output_directory.join(&file_path);
+        let written: io::Result<()> = full_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&full_path, content));
+
+        match written {
+            Ok(()) => {
+                written_files.push_str(&full_path.to_string_lossy());
+                written_files.push('\n');
+            }
+            Err(error) => diagnostics.push(Diagnostic::new(
+                Some(&full_path.to_string_lossy()),
+                &format!("could not write the file: {error}"),
+            )),
+        }
+    }
+
+    written_files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_output_files() {
+        let output_directory: PathBuf = std::env::temp_dir().join(format!("crabodex_test_write_output_files_{}", std::process::id()));
+        let files: Vec<(PathBuf, Vec<u8>)> = vec![
+            (PathBuf::from("index.html"), b"<p>Index</p>".to_vec()),
+            (PathBuf::from("assets/crabodex.css"), b"body {}".to_vec()),
+        ];
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+        let written_files: String = write_output_files(&output_directory, files, &mut diagnostics);
+
+        assert_eq!(written_files.lines().count(), 2);
+        assert_eq!(fs::read_to_string(output_directory.join("assets/crabodex.css")).unwrap(), "body {}");
+        assert!(diagnostics.is_empty());
+        fs::remove_dir_all(output_directory).unwrap();
+    }
+}
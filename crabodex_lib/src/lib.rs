@@ -2,22 +2,95 @@ use std::path::{Path, PathBuf};
 use crate::core::build_doc_structure::build_doc_structure;
 use crate::core::build_navigation::build_navigation;
 use crate::core::build_page_body::build_page_body;
+use crate::core::build_search_index::{build_search_index, search_index_json};
+use crate::core::build_site::build_site;
+use crate::core::build_source_link::{is_edit_branch_missing, is_source_ref_missing};
+use crate::core::check_depth::check_depth;
+use crate::core::config::{Config, ImageMode, OutputFormat};
 use crate::core::create_html_document::create_html_document;
+use crate::core::diagnostic::Diagnostic;
 use crate::core::doc_node::DocNode;
+use crate::core::export_confluence::export_confluence;
+use crate::core::export_epub::export_epub;
+use crate::core::export_json::{export_json, JsonDocument};
+use crate::core::export_latex::export_latex;
+use crate::core::export_llms_text::export_llms_text;
+use crate::core::export_man_page::export_man_page;
+use crate::core::export_markdown::export_markdown;
+use crate::core::export_xml::export_xml;
 use crate::core::find_markdown_files::find_markdown_files;
+use crate::core::import_json::mount_document;
+use crate::core::repository::Repository;
+use crate::core::resolve_includes::resolve_includes;
+use crate::core::write_output_files::write_output_files;
 
 pub mod core;
 
-/// Generates a static documentation website from a directory containing markdown files.
+/// Generates a static documentation website from a directory containing markdown files, with the default configuration.
+///
+/// The warnings raised during the generation are left out, see `generate_with_config` to configure the generation and get them.
+///
+/// # Arguments
+/// * `root_directory` - The directory containing the markdown files.
+/// * `repo_name` - The name of the repository.
+/// * `repo_description` - The description of the repository.
+/// * `commit_hash` - The commit hash of the repository.
+/// * `repo_url` - The URL of the repository.
+/// * `ignore_folders` - The folders to ignore.
+///
+/// # Returns
+/// * `String` - The HTML document as a string.
+///
+/// # Example
+/// ```rust
+/// use crabodex_lib::generate;
+/// use std::path::PathBuf;
+/// let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+///   .join("tests")
+///   .join("test_files");
+/// let result: String = generate(
+///    test_dir,
+///    "Test repository",
+///    "This repository helps us testing crabodex generation.",
+///    "ebb34e7",
+///    "https://github.com/crabodex/crabodex",
+///     &[]
+/// );
+/// ```
+///
+pub fn generate<P: AsRef<Path>>(
+    root_directory: P,
+    repo_name: &str,
+    repo_description: &str,
+    commit_hash: &str,
+    repo_url: &str,
+    ignore_folders: &[String],
+) -> String {
+    let (output, _) = generate_with_config(
+        root_directory,
+        repo_name,
+        repo_description,
+        commit_hash,
+        repo_url,
+        ignore_folders,
+        &Config::default(),
+    );
+    output
+}
+
+/// Generates a static documentation website from a directory containing markdown files, with a configuration.
 /// 
 /// 1. Finds all markdown files in the directory.
-/// 2. Builds a tree structure of the documentation.
+/// 2. Builds a tree structure of the documentation, and warns about the paths deeper than the maximum depth.
+///    The code snippets included in the markdown files are read from their source files.
 /// 3. Generates the navigation bar.
-/// 4. Generates the page body.
-/// 5. Creates the HTML document.
-/// 6. Returns the HTML document as a string.
-/// 7. Ignores folders specified in the `ignore_folders` argument.
-/// 8. The `ignore_folders` argument is optional. If it is not provided, no folders will be ignored.
+/// 4. Generates the page body, with the local images embedded or copied.
+/// 5. Builds the search index of the sections, embedded in the page for the search box.
+/// 6. Creates the HTML document, or exports the tree as JSON or XML, as a single markdown document, as a LaTeX document or as a man page with the `Json`, `Xml`, `Markdown`, `Latex` and `Man` formats of the configuration.
+///    The `Site`, `Llms`, `Epub` and `Confluence` formats write their files into the `output_directory` of the configuration instead.
+/// 7. Returns the document as a string, or the list of the written files, along with the warnings raised during the generation.
+/// 8. Ignores folders specified in the `ignore_folders` argument.
+/// 9. The `ignore_folders` argument is optional. If it is not provided, no folders will be ignored.
 /// 
 /// # Arguments
 /// * `root_directory` - The directory containing the markdown files.
@@ -26,49 +99,173 @@ pub mod core;
 /// * `commit_hash` - The commit hash of the repository.
 /// * `repo_url` - The URL of the repository.
 /// * `ignore_folders` - The folders to ignore.
+/// * `config` - The configuration of the documentation generation.
 /// 
 /// # Returns
-/// * `String` - The HTML document as a string.
+/// * `String` - The HTML document, or the JSON, XML, markdown, LaTeX or man page export, as a string. The paths of the written files for the formats written to a directory.
+/// * `Vec<Diagnostic>` - The warnings raised during the generation.
 /// 
 /// # Example
 /// ```rust
-/// use crabodex_lib::generate;
+/// use crabodex_lib::generate_with_config;
+/// use crabodex_lib::core::config::Config;
 /// use std::path::PathBuf;
 /// let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
 ///   .join("tests")
 ///   .join("test_files");
-/// let result: String = generate(
+/// let (result, diagnostics) = generate_with_config(
 ///    test_dir,
 ///    "Test repository",
 ///    "This repository helps us testing crabodex generation.",
 ///    "ebb34e7",
 ///    "https://github.com/crabodex/crabodex",
-///     &[]
+///     &[],
+///     &Config::default()
 /// );
 /// ```
 /// 
-pub fn generate<P: AsRef<Path>>(
+pub fn generate_with_config<P: AsRef<Path>>(
     root_directory: P,
     repo_name: &str,
     repo_description: &str,
     commit_hash: &str,
     repo_url: &str,
     ignore_folders: &[String],
-) -> String {
+    config: &Config,
+) -> (String, Vec<Diagnostic>) {
     
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
     let markdown_files: Vec<PathBuf> = find_markdown_files(&root_directory, ignore_folders);
-    let doc_structure: DocNode = build_doc_structure(&markdown_files, root_directory.as_ref());
-    let navigation: String = build_navigation(&doc_structure);
-    let page_body: String = build_page_body(&doc_structure, 0, repo_url);
+    let mut doc_structure: DocNode = build_doc_structure(&markdown_files, root_directory.as_ref());
+    resolve_includes(&mut doc_structure, root_directory.as_ref(), &mut diagnostics);
+    check_depth(&doc_structure, config.max_depth, &mut diagnostics);
+    // The source links point at the documented commit, unless another ref is configured
+    let repository: Repository = Repository {
+        name: repo_name.to_string(),
+        description: repo_description.to_string(),
+        url: repo_url.to_string(),
+        commit_hash: commit_hash.to_string(),
+        source_ref: config.source_ref.as_deref().unwrap_or(commit_hash).to_string(),
+        forge: config.forge,
+    };
+    let output: String = render(&doc_structure, root_directory.as_ref(), repository, config, &mut diagnostics);
 
-    create_html_document(
-        &navigation,
-        &page_body,
-        repo_name,
-        repo_description,
-        commit_hash,
-        repo_url
-    )
+    (output, diagnostics)
+}
+
+/// Merges JSON exports of documentations into a single documentation.
+///
+/// 1. Mounts the sections of each export under its mount path, in order.
+///    The sections keep the repository they come from, for their source links, their images and their commit.
+/// 2. Warns about the sections defined more than once, and the paths deeper than the maximum depth.
+/// 3. Renders the merged documentation like `generate_with_config`, in the format of the configuration.
+///
+/// # Arguments
+/// * `root_directory` - The directory the local images of the exports without a repository URL are resolved from.
+/// * `documents` - The exported documentations, with the path each one is mounted under.
+/// * `repo_name` - The name of the merged documentation.
+/// * `repo_description` - The description of the merged documentation.
+/// * `commit_hash` - The commit hash of the repository the merge is made in.
+/// * `repo_url` - The URL of the repository the merge is made in.
+/// * `config` - The configuration of the documentation generation.
+///
+/// # Returns
+/// * `String` - The merged document as a string.
+/// * `Vec<Diagnostic>` - The warnings raised during the merge.
+///
+pub fn merge<P: AsRef<Path>>(
+    root_directory: P,
+    documents: Vec<(JsonDocument, Vec<String>)>,
+    repo_name: &str,
+    repo_description: &str,
+    commit_hash: &str,
+    repo_url: &str,
+    config: &Config,
+) -> (String, Vec<Diagnostic>) {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut doc_structure: DocNode = DocNode::new("Documentation", "");
+    for (document, mount_path) in documents {
+        mount_document(&mut doc_structure, document, &mount_path, &mut diagnostics);
+    }
+    check_depth(&doc_structure, config.max_depth, &mut diagnostics);
+
+    let repository: Repository = Repository {
+        name: repo_name.to_string(),
+        description: repo_description.to_string(),
+        url: repo_url.to_string(),
+        commit_hash: commit_hash.to_string(),
+        source_ref: config.source_ref.as_deref().unwrap_or(commit_hash).to_string(),
+        forge: config.forge,
+    };
+    let output: String = render(&doc_structure, root_directory.as_ref(), repository, config, &mut diagnostics);
+
+    (output, diagnostics)
+}
+
+/// Renders a documentation tree in the format of the configuration.
+fn render(
+    doc_structure: &DocNode,
+    root_directory: &Path,
+    repository: Repository,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    // The images are only copied next to the pages of a site, the documents written to the stdout
+    // embed them instead of writing files to the output directory behind the scenes
+    let is_written_to_stdout: bool = matches!(config.format, OutputFormat::Html | OutputFormat::Json | OutputFormat::Xml);
+    let embed_config: Option<Config> = (config.image_mode == ImageMode::Copy && is_written_to_stdout).then(|| {
+        diagnostics.push(Diagnostic::new(None, "the images are embedded, they are only copied for the `site` format"));
+        Config { image_mode: ImageMode::Embed, ..config.clone() }
+    });
+    let config: &Config = embed_config.as_ref().unwrap_or(config);
+
+    let has_edit_links: bool = matches!(config.format, OutputFormat::Html | OutputFormat::Site | OutputFormat::Epub);
+    if has_edit_links && !repository.url.is_empty() && is_edit_branch_missing(config) {
+        diagnostics.push(Diagnostic::new(None, "the edit links are left out, the branch to edit the files on is unknown"));
+    }
+    if is_source_ref_missing(&repository.url, &repository.source_ref, config) {
+        diagnostics.push(Diagnostic::new(None, "the source links are left out, the ref to link the files at is unknown"));
+    }
+
+    match config.format {
+        OutputFormat::Html => {
+            let navigation: String = build_navigation(doc_structure, config);
+            let page_body: String = build_page_body(doc_structure, root_directory, 0, &repository.url, &repository.source_ref, config, diagnostics);
+            let search_index: String = search_index_json(&build_search_index(doc_structure));
+
+            create_html_document(
+                &navigation,
+                &page_body,
+                &search_index,
+                &repository.name,
+                &repository.description,
+                &repository.commit_hash,
+                &repository.url,
+                config
+            )
+        }
+        OutputFormat::Json => export_json(doc_structure, root_directory, repository, config, diagnostics),
+        OutputFormat::Xml => export_xml(doc_structure, root_directory, &repository, config, diagnostics),
+        OutputFormat::Markdown => export_markdown(doc_structure, &repository, diagnostics),
+        OutputFormat::Latex => export_latex(doc_structure, &repository),
+        OutputFormat::Man => export_man_page(doc_structure, &repository, config, diagnostics),
+        OutputFormat::Site => {
+            let files: Vec<(PathBuf, Vec<u8>)> = build_site(doc_structure, root_directory, &repository, config, diagnostics);
+            write_output_files(&config.output_directory, files, diagnostics)
+        }
+        OutputFormat::Llms => {
+            let files: Vec<(PathBuf, Vec<u8>)> = export_llms_text(doc_structure, &repository, config);
+            write_output_files(&config.output_directory, files, diagnostics)
+        }
+        OutputFormat::Epub => {
+            let files: Vec<(PathBuf, Vec<u8>)> = export_epub(doc_structure, root_directory, &repository, config, diagnostics);
+            write_output_files(&config.output_directory, files, diagnostics)
+        }
+        OutputFormat::Confluence => {
+            let files: Vec<(PathBuf, Vec<u8>)> = export_confluence(doc_structure, &repository);
+            write_output_files(&config.output_directory, files, diagnostics)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -82,15 +279,45 @@ mod tests {
             .join("tests")
             .join("test_files");
 
-        let result: String = generate(
+        let (result, diagnostics): (String, Vec<Diagnostic>) = generate_with_config(
             test_dir,
             "Test repository",
             "This repository helps us testing crabodex generation.",
             "ebb34e7",
             "https://github.com/test_repo",
             &["ignored_test_files".to_string()],
+            &Config::default(),
         );
 
         assert!(result.contains("<title>Test repository</title>"));
+        // The default branch is not known without a git repository
+        assert_eq!(
+            diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>(),
+            ["the edit links are left out, the branch to edit the files on is unknown"]
+        );
+        assert!(!result.contains("edit-link\""));
+    }
+
+    #[test]
+    fn test_copy_images_only_for_site() {
+        let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("test_files");
+        let output_directory: PathBuf = std::env::temp_dir().join(format!("crabodex_test_copy_images_{}", std::process::id()));
+        let config: Config = Config { image_mode: ImageMode::Copy, output_directory: output_directory.clone(), ..Config::default() };
+
+        let (_, diagnostics): (String, Vec<Diagnostic>) = generate_with_config(
+            test_dir,
+            "Test repository",
+            "",
+            "ebb34e7",
+            "",
+            &["ignored_test_files".to_string()],
+            &config,
+        );
+
+        // The page goes to the stdout, nothing is written next to it
+        assert!(!output_directory.exists());
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic.to_string() == "the images are embedded, they are only copied for the `site` format"));
     }
 }
@@ -0,0 +1,11 @@
+/// The maximum discount, in cents.
+pub const MAX_DISCOUNT: u32 = 5_000;
+
+// crabodex:start compute_discount
+pub fn compute_discount(price: u32, rate: u32) -> u32 {
+    // crabodex:start rate
+    let discount: u32 = price * rate / 100;
+    // crabodex:end
+    discount.min(MAX_DISCOUNT)
+}
+// crabodex:end
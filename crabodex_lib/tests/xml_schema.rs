@@ -0,0 +1,243 @@
+//! Validates the XML export against its published XML Schema.
+//!
+//! The schema only uses a small subset of XML Schema: named complex types made of a sequence of
+//! elements and of attributes, and a few built-in simple types. That subset is validated here,
+//! and the schema is rejected if it uses anything else, so it can not drift out of what is checked.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crabodex_lib::core::config::{Config, OutputFormat};
+use crabodex_lib::core::constants::XML_NAMESPACE;
+use crabodex_lib::generate_with_config;
+use roxmltree::{Document, Node};
+
+const XS_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// An element of the sequence of a complex type, with its occurrences, `None` when unbounded.
+struct Particle {
+    name: String,
+    type_name: String,
+    min_occurs: usize,
+    max_occurs: Option<usize>,
+}
+
+/// An attribute of a complex type.
+struct Attribute {
+    name: String,
+    type_name: String,
+    required: bool,
+}
+
+/// A named complex type of the schema.
+#[derive(Default)]
+struct ComplexType {
+    sequence: Vec<Particle>,
+    attributes: Vec<Attribute>,
+}
+
+/// The schema: its root element with its type, and its complex types by name.
+struct Schema {
+    root: (String, String),
+    types: HashMap<String, ComplexType>,
+}
+
+/// The schema constructs of an element, without their documentation.
+fn schema_children<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(|child| {
+        assert!(!child.is_element() || child.tag_name().namespace() == Some(XS_NAMESPACE));
+        child.is_element() && child.tag_name().name() != "annotation"
+    })
+}
+
+/// Reads the attribute of a schema construct, with the attributes it can have.
+fn schema_attribute<'a>(node: Node<'a, '_>, name: &str, allowed: &[&str]) -> Option<&'a str> {
+    for attribute in node.attributes() {
+        assert!(allowed.contains(&attribute.name()), "Unsupported attribute `{}` in the schema", attribute.name());
+    }
+    node.attribute(name)
+}
+
+fn parse_schema(xsd: &Document) -> Schema {
+    let schema: Node = xsd.root_element();
+    assert_eq!(schema.tag_name().namespace(), Some(XS_NAMESPACE));
+    assert_eq!(schema.lookup_namespace_uri(Some("xs")), Some(XS_NAMESPACE));
+    assert_eq!(schema_attribute(schema, "targetNamespace", &["targetNamespace", "elementFormDefault"]), Some(XML_NAMESPACE));
+    assert_eq!(schema.attribute("elementFormDefault"), Some("qualified"));
+
+    let mut root: Option<(String, String)> = None;
+    let mut types: HashMap<String, ComplexType> = HashMap::new();
+    for child in schema_children(schema) {
+        match child.tag_name().name() {
+            "element" => {
+                let name: &str = schema_attribute(child, "name", &["name", "type"]).unwrap();
+                root = Some((name.to_string(), child.attribute("type").unwrap().to_string()));
+            }
+            "complexType" => {
+                let name: &str = schema_attribute(child, "name", &["name"]).unwrap();
+                types.insert(name.to_string(), parse_complex_type(child));
+            }
+            construct => panic!("Unsupported construct `xs:{construct}` in the schema"),
+        }
+    }
+
+    Schema { root: root.unwrap(), types }
+}
+
+fn parse_complex_type(node: Node) -> ComplexType {
+    let mut complex_type: ComplexType = ComplexType::default();
+    for child in schema_children(node) {
+        match child.tag_name().name() {
+            "sequence" => {
+                for element in schema_children(child) {
+                    assert_eq!(element.tag_name().name(), "element");
+                    let allowed: &[&str] = &["name", "type", "minOccurs", "maxOccurs"];
+                    complex_type.sequence.push(Particle {
+                        name: schema_attribute(element, "name", allowed).unwrap().to_string(),
+                        type_name: element.attribute("type").unwrap().to_string(),
+                        min_occurs: element.attribute("minOccurs").map_or(1, |min| min.parse().unwrap()),
+                        max_occurs: match element.attribute("maxOccurs") {
+                            Some("unbounded") => None,
+                            Some(max) => Some(max.parse().unwrap()),
+                            None => Some(1),
+                        },
+                    });
+                }
+            }
+            "attribute" => complex_type.attributes.push(Attribute {
+                name: schema_attribute(child, "name", &["name", "type", "use"]).unwrap().to_string(),
+                type_name: child.attribute("type").unwrap().to_string(),
+                required: child.attribute("use") == Some("required"),
+            }),
+            construct => panic!("Unsupported construct `xs:{construct}` in the schema"),
+        }
+    }
+    complex_type
+}
+
+/// Whether a value is valid for a built-in simple type.
+fn is_valid_value(type_name: &str, value: &str) -> bool {
+    match type_name {
+        "xs:string" => true,
+        "xs:positiveInteger" => value.parse::<u64>().is_ok_and(|number| number > 0),
+        "xs:nonNegativeInteger" => value.parse::<u64>().is_ok(),
+        _ => panic!("Unsupported type `{type_name}` in the schema"),
+    }
+}
+
+/// Validates an element of the document against a type of the schema, recursively.
+fn validate_element(element: Node, type_name: &str, schema: &Schema, errors: &mut Vec<String>) {
+    let name: &str = element.tag_name().name();
+    if element.tag_name().namespace() != Some(XML_NAMESPACE) {
+        errors.push(format!("`{name}` is not in the namespace of the schema"));
+    }
+    let children: Vec<Node> = element.children().filter(Node::is_element).collect();
+
+    let Some(complex_type) = schema.types.get(type_name) else {
+        // The elements of a simple type only have text
+        if !children.is_empty() || element.attributes().next().is_some() {
+            errors.push(format!("`{name}` can only have text"));
+        }
+        if !is_valid_value(type_name, element.text().unwrap_or_default()) {
+            errors.push(format!("`{name}` is not a valid `{type_name}`"));
+        }
+        return;
+    };
+
+    for attribute in element.attributes().filter(|attribute| attribute.namespace() != Some(XSI_NAMESPACE)) {
+        match complex_type.attributes.iter().find(|declared| declared.name == attribute.name()) {
+            Some(declared) if !is_valid_value(&declared.type_name, attribute.value()) => {
+                errors.push(format!("`{}` of `{name}` is not a valid `{}`", attribute.name(), declared.type_name));
+            }
+            Some(_) => {}
+            None => errors.push(format!("`{name}` has an unexpected `{}` attribute", attribute.name())),
+        }
+    }
+    for declared in complex_type.attributes.iter().filter(|declared| declared.required) {
+        if element.attribute(declared.name.as_str()).is_none() {
+            errors.push(format!("`{name}` misses its `{}` attribute", declared.name));
+        }
+    }
+    if element.children().any(|child| child.is_text() && !child.text().unwrap_or_default().trim().is_empty()) {
+        errors.push(format!("`{name}` can not have text"));
+    }
+
+    let mut index: usize = 0;
+    for particle in &complex_type.sequence {
+        let mut count: usize = 0;
+        while index < children.len()
+            && children[index].tag_name().name() == particle.name
+            && particle.max_occurs.is_none_or(|max| count < max)
+        {
+            validate_element(children[index], &particle.type_name, schema, errors);
+            count += 1;
+            index += 1;
+        }
+        if count < particle.min_occurs {
+            errors.push(format!("`{name}` misses its `{}` element", particle.name));
+        }
+    }
+    if let Some(child) = children.get(index) {
+        errors.push(format!("`{name}` has an unexpected `{}` element", child.tag_name().name()));
+    }
+}
+
+/// Validates a document against the schema, returning the errors found.
+fn validate(xml: &str) -> Vec<String> {
+    let xsd_path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("documentation")
+        .join("schema")
+        .join("doc-tree.v1.xsd");
+    let xsd: String = fs::read_to_string(xsd_path).unwrap();
+    let schema: Schema = parse_schema(&Document::parse(&xsd).unwrap());
+    let document: Document = Document::parse(xml).unwrap();
+
+    let mut errors: Vec<String> = Vec::new();
+    let root: Node = document.root_element();
+    if root.tag_name().name() == schema.root.0 {
+        validate_element(root, &schema.root.1, &schema, &mut errors);
+    } else {
+        errors.push(format!("The root element is `{}`", root.tag_name().name()));
+    }
+    errors
+}
+
+#[test]
+fn test_xml_export_follows_the_schema() {
+    let test_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test_files");
+    let config: Config = Config { format: OutputFormat::Xml, ..Config::default() };
+
+    let (xml, _) = generate_with_config(
+        test_dir,
+        "Test repository",
+        "This repository helps us testing crabodex generation.",
+        "ebb34e7",
+        "https://github.com/crabodex/crabodex",
+        &["ignored_test_files".to_string()],
+        &config,
+    );
+
+    assert!(xml.contains("<commit-hash>ebb34e7</commit-hash>"));
+    assert!(xml.contains("<source file=\"file1.md\""));
+    assert_eq!(validate(&xml), Vec::<String>::new());
+}
+
+#[test]
+fn test_schema_rejects_invalid_documents() {
+    let xml: String = format!(
+        "<documentation xmlns=\"{XML_NAMESPACE}\" schema-version=\"0\">\
+         <section slug=\"pricing\" depth=\"1\"><title>Pricing</title></section>\
+         </documentation>"
+    );
+
+    let errors: Vec<String> = validate(&xml);
+
+    assert!(errors.contains(&"`schema-version` of `documentation` is not a valid `xs:positiveInteger`".to_string()));
+    assert!(errors.contains(&"`documentation` misses its `repository` element".to_string()));
+    assert!(errors.contains(&"`section` misses its `path` element".to_string()));
+}